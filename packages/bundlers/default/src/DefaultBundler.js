@@ -5,6 +5,7 @@ import type {
   Bundle,
   BundleGroup,
   Config,
+  Dependency,
   MutableBundleGraph,
   PluginOptions,
 } from '@parcel/types';
@@ -78,11 +79,13 @@ export default (new Bundler({
         let assets = bundleGraph.getDependencyAssets(dependency);
         let resolution = bundleGraph.getResolvedAsset(dependency);
         let bundleGroup = context?.bundleGroup;
-        // Create a new bundle for entries, lazy/parallel dependencies, isolated/inline assets.
+        // Create a new bundle for entries, lazy/parallel dependencies, isolated/inline assets,
+        // and dependencies crossing a client/server boundary.
         if (
           resolution &&
           (!bundleGroup ||
-            dependency.priority === 'lazy' ||
+            isLazy(dependency) ||
+            crossesBoundary(dependency) ||
             dependency.priority === 'parallel' ||
             resolution.bundleBehavior === 'isolated' ||
             resolution.bundleBehavior === 'inline')
@@ -90,11 +93,12 @@ export default (new Bundler({
           let bundleByType: Map<string, Bundle> =
             context?.bundleByType ?? new Map();
 
-          // Only create a new bundle group for entries, lazy dependencies, and isolated assets.
-          // Otherwise, the bundle is loaded together with the parent bundle.
+          // Only create a new bundle group for entries, lazy dependencies, boundaries, and
+          // isolated assets. Otherwise, the bundle is loaded together with the parent bundle.
           if (
             !bundleGroup ||
-            dependency.priority === 'lazy' ||
+            isLazy(dependency) ||
+            crossesBoundary(dependency) ||
             resolution.bundleBehavior === 'isolated'
           ) {
             bundleGroup = bundleGraph.createBundleGroup(
@@ -400,6 +404,21 @@ export default (new Bundler({
   },
 }): Bundler);
 
+// Dynamic imports can be bundled with their importer, e.g.
+// import(/* webpackMode: "eager" */ './a'). They are internalized later,
+// like dynamic imports of assets that are already in the bundle.
+function isLazy(dependency: Dependency): boolean {
+  return (
+    dependency.priority === 'lazy' && dependency.meta.importMode !== 'Eager'
+  );
+}
+
+// Dependencies of a "use client" module compiled for the server, or a "use server" module
+// compiled for the browser, belong to the other graph, so they are not bundled with it.
+function crossesBoundary(dependency: Dependency): boolean {
+  return dependency.meta.boundary != null;
+}
+
 function deduplicate(bundleGraph: MutableBundleGraph) {
   bundleGraph.traverse(node => {
     if (node.type === 'asset') {
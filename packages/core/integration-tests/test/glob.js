@@ -5,6 +5,7 @@ import {
   bundle,
   run,
   assertBundles,
+  findAsset,
   outputFS,
   inputFS,
 } from '@parcel/test-utils';
@@ -133,6 +134,59 @@ describe('glob', function() {
     assert.equal(await output(), 3);
   });
 
+  it('should support require.context', async function() {
+    let b = await bundle(
+      path.join(__dirname, '/integration/glob-context/index.js'),
+    );
+
+    let output = await run(b);
+    assert.deepEqual(output.keys, ['./a.js', './b.js', './nested/c.js']);
+    assert.deepEqual(output.values, [1, 2, 3]);
+    // resolve() returns the id of the module, like webpack.
+    assert.equal(
+      output.id,
+      b.getAssetPublicId(nullthrows(findAsset(b, 'a.js'))),
+    );
+  });
+
+  it('should support import.meta.glob', async function() {
+    let b = await bundle(
+      path.join(__dirname, '/integration/glob-import-meta/index.js'),
+    );
+
+    let output = await run(b);
+    assert.deepEqual(await output(), {
+      keys: ['./dir/a.js', './dir/b.js'],
+      a: 1,
+      eager: {'./dir/a.js': 1, './dir/b.js': 2},
+    });
+  });
+
+  it('should look up partially dynamic import() and require()', async function() {
+    let b = await bundle(
+      path.join(__dirname, '/integration/glob-lookup/index.js'),
+    );
+
+    let output = await run(b);
+    assert.deepEqual(await output('a'), ['a', 'a']);
+    assert.deepEqual(await output('b'), ['b', 'b']);
+  });
+
+  it('should look up the URL of a partially dynamic new URL()', async function() {
+    let b = await bundle(path.join(__dirname, '/integration/glob-url/index.js'));
+
+    let output = await run(b);
+    for (let code of ['fr', 'de']) {
+      assert.equal(
+        output(code),
+        `http://localhost/${path.basename(
+          nullthrows(b.getBundles().find(b => b.name.startsWith(code)))
+            .filePath,
+        )}`,
+      );
+    }
+  });
+
   it('should error when an unsupported asset type imports a glob', async function() {
     let filePath = path.join(__dirname, '/integration/glob-error/index.html');
     // $FlowFixMe
@@ -0,0 +1 @@
+exports.b = 2;
@@ -0,0 +1 @@
+exports.a = 1;
@@ -0,0 +1,6 @@
+var editor = import(/* webpackChunkName: "editor" */ './editor');
+var eager = import(/* webpackMode: "eager" */ './eager');
+
+module.exports = async function () {
+  return (await editor).a + (await eager).b;
+};
@@ -0,0 +1,3 @@
+import {foo} from './a';
+
+export const bar = foo + 1;
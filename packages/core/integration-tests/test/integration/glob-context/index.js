@@ -0,0 +1,7 @@
+const context = require.context('./dir', true, /\.js$/);
+
+module.exports = {
+  keys: context.keys(),
+  values: context.keys().map(key => context(key)),
+  id: context.resolve('./a.js'),
+};
@@ -0,0 +1,10 @@
+const modules = import.meta.glob('./dir/*.js');
+const eager = import.meta.glob('./dir/*.js', { eager: true, import: 'default' });
+
+module.exports = async function () {
+  return {
+    keys: Object.keys(modules),
+    a: (await modules['./dir/a.js']()).default,
+    eager,
+  };
+};
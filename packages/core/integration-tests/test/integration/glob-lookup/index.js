@@ -0,0 +1,3 @@
+module.exports = async function (name) {
+  return [require('./dir/' + name), (await import(`./dir/${name}.js`)).default];
+};
@@ -0,0 +1,3 @@
+module.exports = function (code) {
+  return new URL(`./flags/${code}.svg`, import.meta.url).toString();
+};
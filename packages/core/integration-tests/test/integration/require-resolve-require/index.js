@@ -0,0 +1 @@
+module.exports = require(require.resolve('./other'));
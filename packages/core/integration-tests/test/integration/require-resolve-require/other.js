@@ -0,0 +1,2 @@
+sideEffect('other');
+module.exports = 'other';
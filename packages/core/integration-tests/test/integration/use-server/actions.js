@@ -0,0 +1,6 @@
+'use server';
+import db from './db';
+
+export function save() {
+  return db;
+}
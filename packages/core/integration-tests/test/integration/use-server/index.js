@@ -0,0 +1,3 @@
+import {save} from './actions';
+
+output = save();
@@ -8,6 +8,7 @@ import {
   runBundle,
   runBundles,
   assertBundles,
+  findAsset,
   ncp,
   overlayFS,
   removeDistDirectory,
@@ -2024,6 +2025,105 @@ describe('javascript', function() {
     assert.deepEqual(await (await run(b)).default, [3, 3]);
   });
 
+  it('should replace require.resolve() with the id of the bundled module', async function() {
+    for (let shouldScopeHoist of [false, true]) {
+      let b = await bundle(
+        path.join(__dirname, '/integration/require-resolve/index.js'),
+        {defaultTargetOptions: {shouldScopeHoist}},
+      );
+
+      assertBundles(b, [
+        {
+          name: 'index.js',
+          assets: ['index.js', 'other.js'],
+        },
+      ]);
+
+      let calls = [];
+      let output = await run(b, {sideEffect: name => calls.push(name)});
+      let other = findAsset(b, 'other.js');
+      assert(other);
+      assert.equal(output, b.getAssetPublicId(other));
+      // The module is registered, but not executed.
+      assert.deepEqual(calls, []);
+    }
+  });
+
+  it('should support requiring a module by the id from require.resolve()', async function() {
+    for (let shouldScopeHoist of [false, true]) {
+      let b = await bundle(
+        path.join(__dirname, '/integration/require-resolve-require/index.js'),
+        {defaultTargetOptions: {shouldScopeHoist}},
+      );
+
+      assertBundles(b, [
+        {
+          name: 'index.js',
+          assets: ['index.js', 'other.js'],
+        },
+      ]);
+
+      let calls = [];
+      let output = await run(b, {sideEffect: name => calls.push(name)});
+      assert.equal(output, 'other');
+      assert.deepEqual(calls, ['other']);
+    }
+  });
+
+  it('should name and bundle dynamic imports with magic comments', async () => {
+    let b = await bundle(
+      path.join(__dirname, '/integration/dynamic-magic-comments/index.js'),
+    );
+
+    // The eager import is bundled with its importer, but still returns a promise.
+    assertBundles(b, [
+      {
+        name: 'index.js',
+        assets: [
+          'index.js',
+          'eager.js',
+          'bundle-url.js',
+          'cacheLoader.js',
+          'js-loader.js',
+        ],
+      },
+      {
+        assets: ['editor.js'],
+      },
+    ]);
+
+    let editor = b
+      .getBundles()
+      .find(b => b.getMainEntry()?.filePath.endsWith('editor.js'));
+    assert(/^editor\.[a-f0-9]+\.js$/.test(editor?.name ?? ''));
+    assert.equal(await (await run(b))(), 3);
+  });
+
+  it('should not bundle the dependencies of a "use server" module for the browser', async () => {
+    let b = await bundle(
+      path.join(__dirname, '/integration/use-server/index.js'),
+    );
+
+    assertBundles(b, [
+      {
+        name: 'index.js',
+        assets: [
+          'index.js',
+          'actions.js',
+          'bundle-url.js',
+          'esmodule-helpers.js',
+        ],
+      },
+      {
+        assets: ['db.js', 'esmodule-helpers.js'],
+      },
+    ]);
+
+    // The server module is only referenced by its URL.
+    let res = await run(b, {output: null}, {require: false});
+    assert(/^http:\/\/localhost\/db\.[0-9a-f]+\.js$/.test(res.output));
+  });
+
   it('async dependency internalization successfully removes unneeded bundlegroups and their bundles', async () => {
     let b = await bundle(
       path.join(
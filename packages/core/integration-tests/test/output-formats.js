@@ -1018,6 +1018,43 @@ describe('output formats', function() {
       );
     });
 
+    it('should preserve import and export statements in development builds', async function() {
+      let b = await bundle(
+        path.join(__dirname, '/integration/formats/esm-preserve/index.js'),
+        {
+          mode: 'development',
+          defaultTargetOptions: {
+            outputFormat: 'esmodule',
+            shouldScopeHoist: false,
+          },
+        },
+      );
+
+      let bundles = b.getBundles();
+      assert.equal(bundles.length, 2);
+
+      let entryBundle = nullthrows(
+        bundles.find(b => b.getMainEntry()?.filePath.endsWith('index.js')),
+      );
+      let aBundle = nullthrows(
+        bundles.find(b => b.getMainEntry()?.filePath.endsWith('a.js')),
+      );
+
+      let entry = await outputFS.readFile(entryBundle.filePath, 'utf8');
+      assert(
+        entry.includes(`from "./${path.basename(aBundle.filePath)}"`),
+        'should import the bundle of a.js',
+      );
+      assert(!/function\s*\(require,\s*module,\s*exports/.test(entry));
+
+      let a = await outputFS.readFile(aBundle.filePath, 'utf8');
+      assert(a.includes('export const foo'));
+      assert(!/function\s*\(require,\s*module,\s*exports/.test(a));
+
+      let ns = await run(b);
+      assert.equal(ns.bar, 3);
+    });
+
     it('should support building esmodules with css imports', async function() {
       let b = await bundle(
         path.join(__dirname, '/integration/formats/esm-browser-css/index.html'),
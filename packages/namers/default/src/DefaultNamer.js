@@ -1,6 +1,11 @@
 // @flow strict-local
 
-import type {Bundle, FilePath} from '@parcel/types';
+import type {
+  Bundle,
+  BundleGraph,
+  BundleGroup,
+  FilePath,
+} from '@parcel/types';
 
 import {Namer} from '@parcel/plugin';
 import ThrowableDiagnostic, {md} from '@parcel/diagnostic';
@@ -89,12 +94,14 @@ export default (new Namer({
     // Base split bundle names on the first bundle in their group.
     // e.g. if `index.js` imports `foo.css`, the css bundle should be called
     //      `index.css`.
-    let name = nameFromContent(
-      mainBundle,
-      isEntry,
-      bundleGroup.entryAssetId,
-      bundleGraph.getEntryRoot(bundle.target),
-    );
+    let name =
+      (isEntry ? null : getChunkName(bundleGraph, bundleGroup)) ??
+      nameFromContent(
+        mainBundle,
+        isEntry,
+        bundleGroup.entryAssetId,
+        bundleGraph.getEntryRoot(bundle.target),
+      );
     if (!bundle.needsStableName) {
       name += '.' + bundle.hashReference;
     }
@@ -103,6 +110,22 @@ export default (new Namer({
   },
 }): Namer);
 
+// Dynamic imports can name the bundle they load with a magic comment,
+// e.g. import(/* webpackChunkName: "editor" */ './Editor').
+function getChunkName(
+  bundleGraph: BundleGraph<Bundle>,
+  bundleGroup: BundleGroup,
+): ?string {
+  let entryAsset = bundleGraph.getAssetById(bundleGroup.entryAssetId);
+  for (let dependency of bundleGraph.getIncomingDependencies(entryAsset)) {
+    let chunkName = dependency.meta.chunkName;
+    if (dependency.priority === 'lazy' && typeof chunkName === 'string') {
+      // Keep the name within the dist directory.
+      return chunkName.replace(/[^\w\-/]/g, '-').replace(/^\/+/, '');
+    }
+  }
+}
+
 function nameFromContent(
   bundle: Bundle,
   isEntry: boolean,
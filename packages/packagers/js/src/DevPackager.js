@@ -6,7 +6,11 @@ import SourceMap from '@parcel/source-map';
 import invariant from 'assert';
 import path from 'path';
 import fs from 'fs';
-import {replaceScriptDependencies, getSpecifier} from './utils';
+import {
+  replaceScriptDependencies,
+  replaceModuleDependencies,
+  getSpecifier,
+} from './utils';
 
 const PRELUDE = fs
   .readFileSync(path.join(__dirname, 'dev-prelude.js'), 'utf8')
@@ -56,6 +60,8 @@ export class DevPackager {
     let prefix = this.getPrefix();
     let lineOffset = countLines(prefix);
     let script: ?{|code: string, mapBuffer: ?Buffer|} = null;
+    let mainEntry = this.bundle.getMainEntry();
+    let isModule = mainEntry?.meta.preserveESM === true;
 
     this.bundle.traverse(node => {
       let wrapped = first ? '' : ',';
@@ -86,9 +92,11 @@ export class DevPackager {
 
         // If this is the main entry of a script rather than a module, we need to hoist it
         // outside the bundle wrapper function so that its variables are exposed as globals.
+        // Entries that kept their import and export statements are hoisted as well,
+        // since those are only valid at the top level of a native ES module.
         if (
-          this.bundle.env.sourceType === 'script' &&
-          asset === this.bundle.getMainEntry()
+          (this.bundle.env.sourceType === 'script' || isModule) &&
+          asset === mainEntry
         ) {
           script = results[i++];
           return;
@@ -138,15 +146,16 @@ export class DevPackager {
     });
 
     let entries = this.bundle.getEntryAssets();
-    let mainEntry = this.bundle.getMainEntry();
+    let registeredEntry = mainEntry;
     if (
       (!this.isEntry() && this.bundle.env.outputFormat === 'global') ||
-      this.bundle.env.sourceType === 'script'
+      this.bundle.env.sourceType === 'script' ||
+      isModule
     ) {
       // In async bundles we don't want the main entry to execute until we require it
       // as there might be dependencies in a sibling bundle that hasn't loaded yet.
       entries = entries.filter(a => a.id !== mainEntry?.id);
-      mainEntry = null;
+      registeredEntry = null;
     }
 
     let contents =
@@ -159,7 +168,9 @@ export class DevPackager {
       ) +
       ', ' +
       JSON.stringify(
-        mainEntry ? this.bundleGraph.getAssetPublicId(mainEntry) : null,
+        registeredEntry
+          ? this.bundleGraph.getAssetPublicId(registeredEntry)
+          : null,
       ) +
       ', ' +
       JSON.stringify(this.parcelRequireName) +
@@ -168,14 +179,15 @@ export class DevPackager {
 
     // The entry asset of a script bundle gets hoisted outside the bundle wrapper function
     // so that its variables become globals. We need to replace any require calls for
-    // runtimes with a parcelRequire call.
-    if (this.bundle.env.sourceType === 'script' && script) {
+    // runtimes with a parcelRequire call. ES module entries are hoisted the same way,
+    // and their import placeholders are replaced with the paths of the imported bundles.
+    if (script) {
       let entryMap;
       let mapBuffer = script.mapBuffer;
       if (mapBuffer) {
         entryMap = new SourceMap(this.options.projectRoot, mapBuffer);
       }
-      contents += replaceScriptDependencies(
+      contents += (isModule ? replaceModuleDependencies : replaceScriptDependencies)(
         this.bundleGraph,
         this.bundle,
         script.code,
@@ -28,7 +28,7 @@ const NON_ID_CONTINUE_RE = /[^$_\u200C\u200D\p{ID_Continue}]/gu;
 
 // General regex used to replace imports with the resolved code, references with resolutions,
 // and count the number of newlines in the file for source maps.
-const REPLACEMENT_RE = /\n|import\s+"([0-9a-f]{16}:.+?)";|(?:\$[0-9a-f]{16}\$exports)|(?:\$[0-9a-f]{16}\$(?:import|importAsync|require)\$[0-9a-f]+(?:\$[0-9a-f]+)?)/g;
+const REPLACEMENT_RE = /\n|import\s*"([0-9a-f]{16}:.+?)";|(?:\$[0-9a-f]{16}\$exports)|(?:\$[0-9a-f]{16}\$(?:import|importAsync|require)\$[0-9a-f]+(?:\$[0-9a-f]+)?)/g;
 
 const BUILTINS = Object.keys(globals.builtin);
 const GLOBALS_BY_CONTEXT = {
@@ -494,6 +494,36 @@ export class ScopeHoistingPackager {
       });
     }
 
+    // Modules referenced by require.resolve() have no import statement to replace. They are
+    // wrapped, so only registered, which must happen before this asset's code can require
+    // them. If this asset is wrapped too, they are inserted after it like other dependencies.
+    for (let dep of deps) {
+      if (dep.meta.kind !== 'RequireResolve') {
+        continue;
+      }
+
+      let resolved = this.bundleGraph.getResolvedAsset(dep, this.bundle);
+      if (
+        resolved &&
+        this.bundle.hasAsset(resolved) &&
+        !this.seenAssets.has(resolved.id)
+      ) {
+        if (shouldWrap) {
+          depContent.push(this.visitAsset(resolved));
+        } else {
+          let [depCode, depMap, depLines] = this.visitAsset(resolved);
+          code = depCode + '\n' + code;
+          if (sourceMap) {
+            sourceMap.offsetLines(1, depLines + 1);
+            if (depMap) {
+              sourceMap.addSourceMap(depMap, 0);
+            }
+          }
+          lineCount += depLines + 1;
+        }
+      }
+    }
+
     // If the asset is wrapped, we need to insert the dependency code outside the parcelRequire.register
     // wrapper. Dependencies must be inserted AFTER the asset is registered so that circular dependencies work.
     if (shouldWrap) {
@@ -520,6 +550,15 @@ ${code}
       }
 
       this.needsPrelude = true;
+    } else {
+      for (let [depCode, map, lines] of depContent) {
+        if (!depCode) continue;
+        code += '\n' + depCode;
+        if (sourceMap && map) {
+          sourceMap.addSourceMap(map, lineCount + 1);
+        }
+        lineCount += lines + 1;
+      }
     }
 
     return [code, sourceMap, lineCount];
@@ -8,6 +8,7 @@ import path from 'path';
 import nullthrows from 'nullthrows';
 import {DevPackager} from './DevPackager';
 import {ScopeHoistingPackager} from './ScopeHoistingPackager';
+import {replaceRequireResolveReferences} from './utils';
 
 export default (new Packager({
   async loadConfig({config, options}) {
@@ -60,6 +61,9 @@ export default (new Packager({
           );
 
       ({contents, map} = await packager.package());
+
+      // require.resolve() returns the id of the module, which is only known once bundled.
+      contents = replaceRequireResolveReferences(bundleGraph, bundle, contents);
     }
 
     contents += '\n' + (await getSourceMapSuffix(getSourceMapReference, map));
@@ -2,6 +2,7 @@
 import type {BundleGraph, Dependency, NamedBundle} from '@parcel/types';
 import type SourceMap from '@parcel/source-map';
 import nullthrows from 'nullthrows';
+import {relativeBundlePath} from '@parcel/utils';
 
 // This replaces __parcel__require__ references left by the transformer with
 // parcelRequire calls of the resolved asset id. This lets runtimes work within
@@ -16,20 +17,130 @@ export function replaceScriptDependencies(
   let entry = nullthrows(bundle.getMainEntry());
   let dependencies = bundleGraph.getDependencies(entry);
 
+  return replaceInCode(
+    code,
+    map,
+    /__parcel__require__\(['"](.*?)['"]\)/g,
+    (m, s) => {
+      let dep = nullthrows(dependencies.find(d => getSpecifier(d) === s));
+      let resolved = nullthrows(bundleGraph.getResolvedAsset(dep, bundle));
+      let publicId = bundleGraph.getAssetPublicId(resolved);
+      return `${parcelRequireName}("${publicId}")`;
+    },
+  );
+}
+
+// This replaces the dependency placeholders left in the import and export statements
+// of an entry that preserves ES module syntax with the relative path of the bundle that
+// the imported module is the entry of. Its require calls are replaced with parcelRequire calls,
+// like those of script entries, since the entry is outside the bundle wrapper function.
+export function replaceModuleDependencies(
+  bundleGraph: BundleGraph<NamedBundle>,
+  bundle: NamedBundle,
+  code: string,
+  map: ?SourceMap,
+  parcelRequireName: string,
+): string {
+  let entry = nullthrows(bundle.getMainEntry());
+  let dependencies = bundleGraph.getDependencies(entry);
+
+  return replaceInCode(
+    code,
+    map,
+    /\brequire\(['"](.*?)['"]\)|(['"])([0-9a-f]+)\2/g,
+    (m, specifier, quote, placeholder) => {
+      if (specifier != null) {
+        let dep = dependencies.find(d => getSpecifier(d) === specifier);
+        let resolved = dep && bundleGraph.getResolvedAsset(dep, bundle);
+        if (!resolved) {
+          return m;
+        }
+
+        let publicId = bundleGraph.getAssetPublicId(resolved);
+        return `${parcelRequireName}("${publicId}")`;
+      }
+
+      // Other placeholders, e.g. of require.resolve(), are replaced separately.
+      let dep = dependencies.find(d => d.meta.placeholder === placeholder);
+      if (!dep || dep.meta.preserveESM !== true) {
+        return m;
+      }
+
+      // Modules that preserve ES module syntax are the entries of their own bundles,
+      // while CommonJS modules are bundled with their importer and can't be imported natively.
+      let referenced = bundleGraph.getReferencedBundle(dep, bundle);
+      if (
+        !referenced ||
+        (referenced.type === 'js' &&
+          referenced.getMainEntry()?.meta.preserveESM !== true)
+      ) {
+        throw new Error(
+          `${dep.specifier} must be an ES module to be imported by ${entry.filePath}, which preserves ES module syntax.`,
+        );
+      }
+
+      return JSON.stringify(relativeBundlePath(bundle, referenced));
+    },
+  );
+}
+
+// This replaces the placeholders left by require.resolve() calls with the public id of
+// the resolved module, which is registered in this bundle or one of its ancestors.
+export function replaceRequireResolveReferences(
+  bundleGraph: BundleGraph<NamedBundle>,
+  bundle: NamedBundle,
+  contents: string,
+): string {
+  let replacements = new Map();
+  bundle.traverse(node => {
+    if (
+      node.type !== 'dependency' ||
+      node.value.meta.kind !== 'RequireResolve'
+    ) {
+      return;
+    }
+
+    let dep = node.value;
+    let resolved = bundleGraph.getResolvedAsset(dep, bundle);
+    let placeholder = dep.meta.placeholder;
+    if (resolved && typeof placeholder === 'string') {
+      replacements.set(
+        JSON.stringify(placeholder),
+        JSON.stringify(bundleGraph.getAssetPublicId(resolved)),
+      );
+    }
+  });
+
+  if (replacements.size === 0) {
+    return contents;
+  }
+
+  // Placeholders are hex strings, so they don't need to be escaped.
+  let regex = new RegExp([...replacements.keys()].join('|'), 'g');
+  return contents.replace(regex, m => nullthrows(replacements.get(m)));
+}
+
+// Replaces each match of `regex` in the code, offsetting the columns of the source map
+// after replacements that change the length of the line.
+function replaceInCode(
+  code: string,
+  map: ?SourceMap,
+  regex: RegExp,
+  replacer: (m: string, ...groups: Array<?string>) => string,
+): string {
   let lineCount = 0;
   let offset = 0;
   let columnStartIndex = 0;
-  code = code.replace(/\n|__parcel__require__\(['"](.*?)['"]\)/g, (m, s, i) => {
+  let pattern = new RegExp(`\\n|${regex.source}`, 'g');
+  return code.replace(pattern, (m, ...args) => {
+    let i = args[args.length - 2];
     if (m === '\n') {
       columnStartIndex = i + offset + 1;
       lineCount++;
       return '\n';
     }
 
-    let dep = nullthrows(dependencies.find(d => getSpecifier(d) === s));
-    let resolved = nullthrows(bundleGraph.getResolvedAsset(dep, bundle));
-    let publicId = bundleGraph.getAssetPublicId(resolved);
-    let replacement = `${parcelRequireName}("${publicId}")`;
+    let replacement = replacer(m, ...args.slice(0, -2));
     if (map) {
       let lengthDifference = replacement.length - m.length;
       if (lengthDifference !== 0) {
@@ -44,8 +155,6 @@ export function replaceScriptDependencies(
 
     return replacement;
   });
-
-  return code;
 }
 
 export function getSpecifier(dep: Dependency): string {
@@ -4,6 +4,7 @@ import {isGlob, glob, relativePath, normalizeSeparators} from '@parcel/utils';
 import micromatch from 'micromatch';
 import path from 'path';
 import nullthrows from 'nullthrows';
+import invariant from 'assert';
 import ThrowableDiagnostic from '@parcel/diagnostic';
 
 export default (new Resolver({
@@ -64,7 +65,64 @@ export default (new Resolver({
     });
 
     let code = '';
-    if (sourceAssetType === 'js') {
+    let query;
+    let glob = dependency.meta?.glob;
+    if (sourceAssetType === 'js' && glob != null && typeof glob === 'object') {
+      switch (glob.type) {
+        case 'ImportMetaGlob':
+          code = generateImportMetaGlob(
+            results,
+            path.dirname(sourceFile),
+            Boolean(glob.eager),
+            typeof glob.import === 'string' ? glob.import : null,
+          );
+          break;
+        case 'RequireContext': {
+          let {directory, regexp, flags} = glob;
+          invariant(
+            typeof directory === 'string' &&
+              typeof regexp === 'string' &&
+              typeof flags === 'string',
+          );
+          code = generateRequireContext(
+            results,
+            path.resolve(path.dirname(sourceFile), directory),
+            // Global and sticky regexps are stateful, which would skip matches.
+            new RegExp(regexp, flags.replace(/[gy]/g, '')),
+            glob.lazy ? 'import' : 'require',
+            false,
+          );
+          break;
+        }
+        case 'Lookup':
+          // Partially dynamic import() or require(), e.g. import(`./locales/${lang}.json`).
+          // The specifier is looked up at runtime by the path relative to the importer.
+          code = generateRequireContext(
+            results,
+            path.dirname(sourceFile),
+            /(?:)/,
+            glob.lazy ? 'import' : 'require',
+            // e.g. require('./icons/' + name) may omit the extension.
+            specifier.endsWith('*'),
+          );
+          break;
+        case 'Url':
+          // new URL(`./flags/${code}.svg`, import.meta.url) looks up the URL of the asset.
+          code = generateRequireContext(
+            results,
+            path.dirname(sourceFile),
+            /(?:)/,
+            'url',
+            false,
+          );
+          break;
+        default:
+          throw new Error(`Unknown glob type ${String(glob.type)}`);
+      }
+
+      // Keep the modules generated with different options apart.
+      query = new URLSearchParams({glob: JSON.stringify(glob)});
+    } else if (sourceAssetType === 'js') {
       let re = micromatch.makeRe(normalized, {capture: true});
       let matches = {};
       for (let [file, relative] of results) {
@@ -93,6 +151,7 @@ export default (new Resolver({
           sourceAssetType,
       ),
       code,
+      query,
       invalidateOnFileCreate: [{glob: normalized}],
       pipeline: null,
       priority: 'sync',
@@ -100,6 +159,107 @@ export default (new Resolver({
   },
 }): Resolver);
 
+// Generates the object returned by import.meta.glob(), keyed by the path of each
+// file relative to the importer, like Vite.
+function generateImportMetaGlob(results, importerDir, eager, name) {
+  let imports = '';
+  let res = '{';
+  let sorted = [...results].sort(([a], [b]) => (a < b ? -1 : a > b ? 1 : 0));
+  for (let i = 0; i < sorted.length; i++) {
+    let [file, relative] = sorted[i];
+    let key = relativePath(importerDir, file);
+    let specifier = JSON.stringify(relative);
+    let value;
+    if (eager) {
+      imports += `const _temp${i} = require(${specifier});\n`;
+      value = name != null ? `_temp${i}[${JSON.stringify(name)}]` : `_temp${i}`;
+    } else {
+      value = `() => import(${specifier})`;
+      if (name != null) {
+        value += `.then(m => m[${JSON.stringify(name)}])`;
+      }
+    }
+
+    res += `\n  ${JSON.stringify(key)}: ${value},`;
+  }
+
+  return imports + 'module.exports = ' + res + '\n};';
+}
+
+// Generates a webpack compatible require.context() function for the files in a
+// directory whose path relative to it matches the regexp. It loads each file with
+// require(), import() or new URL(). With extensionless, each file can also be looked
+// up without its extension. Like webpack, only synchronous contexts have resolve(),
+// which returns the id of the module.
+function generateRequireContext(
+  results,
+  contextDir,
+  regexp,
+  load: 'require' | 'import' | 'url',
+  extensionless,
+) {
+  let lazy = load === 'import';
+  let entries = new Map();
+  let sorted = [...results].sort(([a], [b]) => (a < b ? -1 : a > b ? 1 : 0));
+  for (let [file, relative] of sorted) {
+    let key = relativePath(contextDir, file);
+    if (!regexp.test(key)) {
+      continue;
+    }
+
+    entries.set(key, JSON.stringify(relative));
+  }
+
+  if (extensionless) {
+    for (let [key, specifier] of [...entries]) {
+      let ext = path.extname(key);
+      let withoutExt = key.slice(0, key.length - ext.length);
+      if (ext && !entries.has(withoutExt)) {
+        entries.set(withoutExt, specifier);
+      }
+    }
+  }
+
+  let map = '{';
+  let ids = '{';
+  for (let [key, specifier] of entries) {
+    let value =
+      load === 'url'
+        ? `new URL(${specifier}, import.meta.url)`
+        : `${load}(${specifier})`;
+    map += `\n  ${JSON.stringify(key)}: () => ${value},`;
+    ids += `\n  ${JSON.stringify(key)}: require.resolve(${specifier}),`;
+  }
+  map += '\n}';
+  ids += '\n}';
+
+  let code = `const map = ${map};
+function lookup(key) {
+  if (!Object.prototype.hasOwnProperty.call(map, key)) {
+    let err = new Error("Cannot find module '" + key + "'");
+    err.code = 'MODULE_NOT_FOUND';
+    throw err;
+  }
+  return map[key];
+}
+function context(key) {
+  ${lazy ? 'return Promise.resolve().then(() => lookup(key)());' : 'return lookup(key)();'}
+}
+context.keys = () => Object.keys(map);
+`;
+
+  if (load === 'require') {
+    code += `const ids = ${ids};
+context.resolve = key => {
+  lookup(key);
+  return ids[key];
+};
+`;
+  }
+
+  return code + 'module.exports = context;\n';
+}
+
 function set(obj, path, value) {
   for (let i = 0; i < path.length - 1; i++) {
     let part = path[i];
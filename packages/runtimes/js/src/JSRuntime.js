@@ -261,7 +261,13 @@ function getDependencies(
         return;
       }
 
+      // Imports kept by modules that preserve ES module syntax are loaded natively,
+      // so they don't need a runtime. See replaceModuleDependencies in the JS packager.
       let dependency = node.value;
+      if (dependency.meta.preserveESM === true) {
+        return;
+      }
+
       if (
         dependency.priority === 'lazy' &&
         dependency.specifierType !== 'url'
@@ -429,20 +435,27 @@ function getHintedBundleGroups(
   let prefetch = [];
   let {asyncDependencies} = getDependencies(bundle);
   for (let dependency of asyncDependencies) {
+    // The JS transformer sets the resource hint from import('./a', {preload: true}) and magic
+    // comments such as /* webpackPrefetch: true */. Other transformers may set attributes instead.
     let attributes = dependency.meta?.importAttributes;
-    if (
-      typeof attributes === 'object' &&
-      attributes != null &&
+    let resourceHint = dependency.meta?.resourceHint;
+    let hasAttributes = typeof attributes === 'object' && attributes != null;
+    // === true for flow
+    let isPreload =
+      resourceHint === 'Preload' ||
       // $FlowFixMe
-      (attributes.preload || attributes.prefetch)
-    ) {
+      (hasAttributes && attributes.preload === true);
+    let isPrefetch =
+      resourceHint === 'Prefetch' ||
+      // $FlowFixMe
+      (hasAttributes && attributes.prefetch === true);
+    if (isPreload || isPrefetch) {
       let resolved = bundleGraph.resolveAsyncDependency(dependency, bundle);
       if (resolved?.type === 'bundle_group') {
-        // === true for flow
-        if (attributes.preload === true) {
+        if (isPreload) {
           preload.push(resolved.value);
         }
-        if (attributes.prefetch === true) {
+        if (isPrefetch) {
           prefetch.push(resolved.value);
         }
       }
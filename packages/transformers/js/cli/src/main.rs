@@ -0,0 +1,171 @@
+extern crate parcel_js_swc_core;
+extern crate serde_json;
+
+use std::path::Path;
+use std::process;
+
+use serde_json::{json, Value};
+
+const USAGE: &str = "Usage: parcel-swc <file> [options]
+
+Transforms a file with the Parcel JS transformer and prints the result.
+
+Options:
+  -c, --config <file>   JSON file with transformer options, merged over the defaults
+  --code                Print the transformed code
+  --map                 Print the source map
+  --dependencies        Print the dependencies
+  --hoist-result        Print the scope hoisting result
+  --diagnostics         Print the diagnostics
+  --json                Print the selected parts as a single JSON object
+  -h, --help            Print this message
+
+When no parts are selected, all of them are printed.";
+
+const PARTS: [&str; 5] = ["code", "map", "dependencies", "hoist_result", "diagnostics"];
+
+fn main() {
+  let mut file = None;
+  let mut config_file = None;
+  let mut parts = vec![];
+  let mut as_json = false;
+
+  let mut args = std::env::args().skip(1);
+  while let Some(arg) = args.next() {
+    match arg.as_str() {
+      "-c" | "--config" => match args.next() {
+        Some(path) => config_file = Some(path),
+        None => usage_error("Missing value for --config"),
+      },
+      "--code" => parts.push("code"),
+      "--map" => parts.push("map"),
+      "--dependencies" => parts.push("dependencies"),
+      "--hoist-result" => parts.push("hoist_result"),
+      "--diagnostics" => parts.push("diagnostics"),
+      "--json" => as_json = true,
+      "-h" | "--help" => {
+        println!("{}", USAGE);
+        return;
+      }
+      _ if arg.starts_with('-') => usage_error(&format!("Unknown option {}", arg)),
+      _ if file.is_none() => file = Some(arg),
+      _ => usage_error(&format!("Unexpected argument {}", arg)),
+    }
+  }
+
+  let file = match file {
+    Some(file) => file,
+    None => usage_error("Missing input file"),
+  };
+  if parts.is_empty() {
+    parts = PARTS.to_vec();
+  }
+
+  let code =
+    std::fs::read(&file).unwrap_or_else(|err| fail(&format!("Could not read {}: {}", file, err)));
+  let filename = std::fs::canonicalize(&file).unwrap_or_else(|_| Path::new(&file).to_path_buf());
+  let project_root = std::env::current_dir().unwrap_or_default();
+
+  let mut config = default_config();
+  config["filename"] = json!(filename.to_string_lossy());
+  config["code"] = json!(code);
+  config["project_root"] = json!(project_root.to_string_lossy());
+  let extension = filename.extension().and_then(|ext| ext.to_str());
+  config["is_type_script"] = json!(matches!(extension, Some("ts") | Some("tsx")));
+  config["is_jsx"] = json!(matches!(extension, Some("jsx") | Some("tsx")));
+  if let Some(config_file) = config_file {
+    let contents = std::fs::read_to_string(&config_file)
+      .unwrap_or_else(|err| fail(&format!("Could not read {}: {}", config_file, err)));
+    let overrides: Value = serde_json::from_str(&contents)
+      .unwrap_or_else(|err| fail(&format!("Invalid config {}: {}", config_file, err)));
+    if !overrides.is_object() {
+      fail(&format!(
+        "Invalid config {}: expected an object",
+        config_file
+      ));
+    }
+    merge(&mut config, overrides);
+  }
+
+  let config: parcel_js_swc_core::Config =
+    serde_json::from_value(config).unwrap_or_else(|err| fail(&format!("Invalid config: {}", err)));
+  let result = parcel_js_swc_core::transform(config)
+    .unwrap_or_else(|err| fail(&format!("Transform failed: {}", err)));
+  let mut result = serde_json::to_value(&result).unwrap();
+
+  // The code is serialized as a byte array.
+  let code: Vec<u8> = result["code"]
+    .as_array()
+    .map(|bytes| {
+      bytes
+        .iter()
+        .filter_map(|b| b.as_u64().map(|b| b as u8))
+        .collect()
+    })
+    .unwrap_or_default();
+  result["code"] = json!(String::from_utf8_lossy(&code));
+
+  if as_json {
+    let mut output = serde_json::Map::new();
+    for part in &parts {
+      output.insert(part.to_string(), result[part].take());
+    }
+    println!("{}", serde_json::to_string_pretty(&output).unwrap());
+  } else {
+    for part in &parts {
+      let value = &result[part];
+      if parts.len() > 1 {
+        println!("=== {} ===", part);
+      }
+      match value {
+        Value::String(s) => println!("{}", s),
+        Value::Null => println!("null"),
+        value => println!("{}", serde_json::to_string_pretty(value).unwrap()),
+      }
+    }
+  }
+
+  let has_errors = match result["diagnostics"].as_array() {
+    Some(diagnostics) => diagnostics
+      .iter()
+      .any(|d| d["severity"] == "Error" || d["severity"] == "SourceError"),
+    None => false,
+  };
+  if has_errors {
+    process::exit(1);
+  }
+}
+
+/// Options for a browser module with scope hoisting disabled, similar to a Parcel
+/// development build. Options that are not listed use `Config::default()`.
+/// Any of them can be overridden by the config file.
+fn default_config() -> Value {
+  let mut config = serde_json::to_value(parcel_js_swc_core::Config::default()).unwrap();
+  let overrides = json!({
+    "module_id": "0",
+    "is_browser": true,
+    "is_development": true,
+    "source_maps": true,
+    "detect_source_map_url": true
+  });
+  merge(&mut config, overrides);
+  config
+}
+
+/// Replaces the options in `config` with the ones in `overrides`.
+fn merge(config: &mut Value, overrides: Value) {
+  if let Value::Object(overrides) = overrides {
+    for (key, value) in overrides {
+      config[key] = value;
+    }
+  }
+}
+
+fn usage_error(message: &str) -> ! {
+  fail(&format!("{}\n\n{}", message, USAGE))
+}
+
+fn fail(message: &str) -> ! {
+  eprintln!("{}", message);
+  process::exit(1);
+}
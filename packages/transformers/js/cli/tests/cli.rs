@@ -0,0 +1,54 @@
+extern crate serde_json;
+
+use std::path::Path;
+use std::process::Command;
+
+use serde_json::Value;
+
+fn run(args: &[&str]) -> (bool, String) {
+  let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+  let output = Command::new(env!("CARGO_BIN_EXE_parcel-swc"))
+    .current_dir(fixtures)
+    .args(args)
+    .output()
+    .unwrap();
+  (
+    output.status.success(),
+    String::from_utf8(output.stdout).unwrap(),
+  )
+}
+
+#[test]
+fn json_output() {
+  let (success, stdout) = run(&[
+    "index.js",
+    "-c",
+    "config.json",
+    "--code",
+    "--dependencies",
+    "--diagnostics",
+    "--json",
+  ]);
+  assert!(success);
+
+  let output: Value = serde_json::from_str(&stdout).unwrap();
+  let keys: Vec<_> = output.as_object().unwrap().keys().collect();
+  assert_eq!(keys, ["code", "dependencies", "diagnostics"]);
+
+  let code = output["code"].as_str().unwrap();
+  assert!(code.contains("import \"abc:./math\";"));
+  assert!(code.contains("const $abc$export$"));
+
+  let dependencies = output["dependencies"].as_array().unwrap();
+  assert_eq!(dependencies.len(), 1);
+  assert_eq!(dependencies[0]["kind"], "Import");
+  assert_eq!(dependencies[0]["specifier"], "./math");
+  assert_eq!(output["diagnostics"], Value::Null);
+}
+
+#[test]
+fn invalid_config() {
+  let (success, stdout) = run(&["index.js", "-c", "index.js"]);
+  assert!(!success);
+  assert!(stdout.is_empty());
+}
@@ -0,0 +1,3 @@
+import {add} from './math';
+
+export const sum = add(1, 2);
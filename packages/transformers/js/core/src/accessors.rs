@@ -0,0 +1,153 @@
+use swc_common::{BytePos, SourceFile, Span};
+use swc_ecmascript::parser::lexer::Lexer;
+use swc_ecmascript::parser::token::{Keyword, Token, TokenAndSpan, Word};
+use swc_ecmascript::parser::{StringInput, Syntax};
+
+/// The parser does not support auto-accessors yet, e.g. `accessor x = 1`. This finds the
+/// `accessor` keywords of class members, and returns the code with them replaced by spaces
+/// along with the start of the name of each member. The members then parse as fields,
+/// without changing the position of any other token, and the standard decorators pass
+/// turns them into accessors.
+pub fn strip_accessors(source_file: &SourceFile, syntax: Syntax) -> Option<(String, Vec<BytePos>)> {
+  if !source_file.src.contains("accessor") {
+    return None;
+  }
+
+  let lexer = Lexer::new(
+    syntax,
+    Default::default(),
+    StringInput::from(source_file),
+    None,
+  );
+  let tokens: Vec<TokenAndSpan> = lexer.collect();
+  let (keywords, names) = find_accessors(&tokens);
+  if keywords.is_empty() {
+    return None;
+  }
+
+  let mut code = source_file.src.to_string();
+  for span in keywords {
+    let start = (span.lo - source_file.start_pos).0 as usize;
+    let end = (span.hi - source_file.start_pos).0 as usize;
+    code.replace_range(start..end, &" ".repeat(end - start));
+  }
+
+  Some((code, names))
+}
+
+enum Delimiter {
+  ClassBody,
+  Other,
+}
+
+/// Returns the span of each `accessor` keyword, and the start of the token after it.
+fn find_accessors(tokens: &[TokenAndSpan]) -> (Vec<Span>, Vec<BytePos>) {
+  let mut keywords = vec![];
+  let mut names = vec![];
+  let mut stack = vec![];
+  // The depth of the delimiter stack at a `class` keyword, until its body starts.
+  let mut class_depth = None;
+
+  for (i, token) in tokens.iter().enumerate() {
+    let next = tokens.get(i + 1);
+    match &token.token {
+      Token::Word(Word::Keyword(Keyword::Class)) => {
+        // Skip member names such as `obj.class` or `{ class: 1 }`.
+        let is_member = i > 0 && tokens[i - 1].token == Token::Dot;
+        let starts_class = matches!(
+          next.map(|next| &next.token),
+          Some(Token::Word(_)) | Some(Token::LBrace)
+        );
+        if !is_member && starts_class {
+          class_depth = Some(stack.len());
+        }
+      }
+      Token::LBrace if class_depth == Some(stack.len()) => {
+        class_depth = None;
+        stack.push(Delimiter::ClassBody);
+      }
+      Token::LBrace | Token::DollarLBrace | Token::LParen | Token::LBracket => {
+        stack.push(Delimiter::Other)
+      }
+      Token::RBrace | Token::RParen | Token::RBracket => {
+        stack.pop();
+      }
+      // Otherwise, `accessor` is the name of a member, e.g. `accessor = 1` or `accessor() {}`.
+      Token::Word(Word::Ident(name))
+        if *name == *"accessor" && matches!(stack.last(), Some(Delimiter::ClassBody)) =>
+      {
+        if let Some(next) = next {
+          let is_name = matches!(
+            next.token,
+            Token::Word(_)
+              | Token::Str { .. }
+              | Token::Num(_)
+              | Token::BigInt(_)
+              | Token::LBracket
+              | Token::Hash
+          );
+          if is_name && !next.had_line_break {
+            keywords.push(token.span);
+            names.push(next.span.lo);
+          }
+        }
+      }
+      _ => {}
+    }
+  }
+
+  (keywords, names)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use swc_common::{FileName, SourceMap};
+  use swc_ecmascript::parser::EsConfig;
+
+  fn strip(code: &str) -> Option<(String, Vec<u32>)> {
+    let source_map = SourceMap::default();
+    let source_file = source_map.new_source_file(FileName::Anon, code.into());
+    let syntax = Syntax::Es(EsConfig {
+      decorators: true,
+      ..Default::default()
+    });
+    strip_accessors(&source_file, syntax).map(|(code, names)| {
+      let names = names
+        .iter()
+        .map(|pos| (*pos - source_file.start_pos).0)
+        .collect();
+      (code, names)
+    })
+  }
+
+  #[test]
+  fn accessors() {
+    assert_eq!(
+      strip("class A { accessor x = 1; @dec static accessor #y; accessor [z]; }"),
+      Some((
+        "class A {          x = 1; @dec static          #y;          [z]; }".into(),
+        vec![19, 47, 60]
+      ))
+    );
+    assert_eq!(
+      strip("let A = class extends (f()) { accessor 'x'; m() { class B { accessor 1n } } };"),
+      Some((
+        "let A = class extends (f()) {          'x'; m() { class B {          1n } } };".into(),
+        vec![39, 69]
+      ))
+    );
+  }
+
+  #[test]
+  fn not_accessors() {
+    assert_eq!(strip("let accessor = 1;"), None);
+    assert_eq!(strip("let o = { accessor: 1, a: obj.class };"), None);
+    assert_eq!(
+      strip("class A { accessor = 1; accessor() {} static accessor; m() { return { accessor x() {} }; } }"),
+      None
+    );
+    // A line break ends a field named `accessor`.
+    assert_eq!(strip("class A { accessor\n x = 1; }"), None);
+  }
+}
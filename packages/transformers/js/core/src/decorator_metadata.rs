@@ -0,0 +1,817 @@
+use std::collections::HashMap;
+
+use swc_atoms::JsWord;
+use swc_common::DUMMY_SP;
+use swc_ecmascript::ast::*;
+use swc_ecmascript::utils::find_ids;
+use swc_ecmascript::visit::{Fold, FoldWith};
+
+/// This pass adds the `design:type`, `design:paramtypes` and `design:returntype` metadata
+/// that TSC emits for decorated classes and members with the `emitDecoratorMetadata` option.
+/// It must run before the legacy decorators pass, while type annotations are still present.
+/// Constructor parameter decorators are also converted to class decorators, like TSC does.
+///
+/// Types are serialized using the declarations in this module only. Imported names could be
+/// classes, interfaces or enums, which cannot be known from a single file, so they are guarded
+/// with `typeof`. Referencing them also keeps their imports when types are stripped.
+pub fn decorator_metadata() -> impl Fold {
+  DecoratorMetadata {
+    bindings: HashMap::new(),
+    type_params: vec![],
+    resolving: vec![],
+    class_name: None,
+  }
+}
+
+enum Binding {
+  /// A local class, function or variable, which can be referenced directly.
+  Value,
+  /// An interface, or a name imported with `import type`.
+  Type,
+  /// A type alias, which is serialized as the aliased type.
+  Alias(Box<TsType>),
+  /// An enum, serialized as `Number`, `String` or `Object` depending on its members.
+  Enum(&'static str),
+  /// A regular import, which may or may not have a runtime value.
+  Import,
+}
+
+struct DecoratorMetadata {
+  bindings: HashMap<JsWord, Binding>,
+  type_params: Vec<JsWord>,
+  resolving: Vec<JsWord>,
+  class_name: Option<JsWord>,
+}
+
+impl Fold for DecoratorMetadata {
+  fn fold_module(&mut self, node: Module) -> Module {
+    self.collect_bindings(&node);
+    node.fold_children_with(self)
+  }
+
+  fn fold_class_decl(&mut self, node: ClassDecl) -> ClassDecl {
+    let class_name = self.class_name.replace(node.ident.sym.clone());
+    let mut node = node.fold_children_with(self);
+    self.add_metadata(&mut node.class);
+    self.class_name = class_name;
+    node
+  }
+
+  fn fold_class_expr(&mut self, node: ClassExpr) -> ClassExpr {
+    let class_name = std::mem::replace(
+      &mut self.class_name,
+      node.ident.as_ref().map(|ident| ident.sym.clone()),
+    );
+    let mut node = node.fold_children_with(self);
+    self.add_metadata(&mut node.class);
+    self.class_name = class_name;
+    node
+  }
+}
+
+impl DecoratorMetadata {
+  fn collect_bindings(&mut self, module: &Module) {
+    for item in &module.body {
+      let decl = match item {
+        ModuleItem::ModuleDecl(ModuleDecl::Import(import)) => {
+          for specifier in &import.specifiers {
+            let local = match specifier {
+              ImportSpecifier::Named(named) => &named.local,
+              ImportSpecifier::Default(default) => &default.local,
+              ImportSpecifier::Namespace(namespace) => &namespace.local,
+            };
+            let binding = if import.type_only {
+              Binding::Type
+            } else {
+              Binding::Import
+            };
+            self.bindings.insert(local.sym.clone(), binding);
+          }
+          continue;
+        }
+        ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(export)) => &export.decl,
+        ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultDecl(ExportDefaultDecl {
+          decl: DefaultDecl::Class(ClassExpr {
+            ident: Some(ident), ..
+          }),
+          ..
+        })) => {
+          self.bindings.insert(ident.sym.clone(), Binding::Value);
+          continue;
+        }
+        ModuleItem::Stmt(Stmt::Decl(decl)) => decl,
+        _ => continue,
+      };
+
+      match decl {
+        Decl::Class(class) => {
+          self
+            .bindings
+            .insert(class.ident.sym.clone(), Binding::Value);
+        }
+        Decl::Fn(func) => {
+          self.bindings.insert(func.ident.sym.clone(), Binding::Value);
+        }
+        Decl::Var(var) => {
+          let ids: Vec<Ident> = find_ids(&var.decls);
+          for id in ids {
+            self.bindings.insert(id.sym, Binding::Value);
+          }
+        }
+        Decl::TsModule(module) => {
+          if let TsModuleName::Ident(ident) = &module.id {
+            self.bindings.insert(ident.sym.clone(), Binding::Value);
+          }
+        }
+        Decl::TsInterface(interface) => {
+          // Interfaces can be merged with a class of the same name.
+          self
+            .bindings
+            .entry(interface.id.sym.clone())
+            .or_insert(Binding::Type);
+        }
+        Decl::TsTypeAlias(alias) => {
+          self
+            .bindings
+            .insert(alias.id.sym.clone(), Binding::Alias(alias.type_ann.clone()));
+        }
+        Decl::TsEnum(enum_decl) => {
+          self.bindings.insert(
+            enum_decl.id.sym.clone(),
+            Binding::Enum(enum_kind(enum_decl)),
+          );
+        }
+      }
+    }
+  }
+
+  fn add_metadata(&mut self, class: &mut Class) {
+    let type_params_len = self.type_params.len();
+    if let Some(type_params) = &class.type_params {
+      self.type_params.extend(
+        type_params
+          .params
+          .iter()
+          .map(|param| param.name.sym.clone()),
+      );
+    }
+
+    // Constructor parameter decorators are applied as class decorators,
+    // e.g. `@Inject(Foo) foo` becomes `function (target) { return Inject(Foo)(target, undefined, 0) }`.
+    let mut param_types = None;
+    for member in &mut class.body {
+      if let ClassMember::Constructor(constructor) = member {
+        for (index, param) in constructor.params.iter_mut().enumerate() {
+          let decorators = match param {
+            ParamOrTsParamProp::TsParamProp(prop) => &mut prop.decorators,
+            ParamOrTsParamProp::Param(param) => &mut param.decorators,
+          };
+          for decorator in decorators.drain(..) {
+            class
+              .decorators
+              .push(create_param_decorator(decorator, index));
+          }
+        }
+
+        if constructor.body.is_some() {
+          param_types = Some(
+            constructor
+              .params
+              .iter()
+              .filter_map(|param| match param {
+                ParamOrTsParamProp::TsParamProp(prop) => match &prop.param {
+                  TsParamPropParam::Ident(ident) => {
+                    Some(ident.type_ann.as_ref().map(|t| (*t.type_ann).clone()))
+                  }
+                  TsParamPropParam::Assign(assign) => Some(
+                    assign
+                      .type_ann
+                      .as_ref()
+                      .map(|t| &*t.type_ann)
+                      .or_else(|| type_of_pat(&assign.left))
+                      .cloned(),
+                  ),
+                },
+                ParamOrTsParamProp::Param(param) => param_type(&param.pat).map(|ty| ty.cloned()),
+              })
+              .collect::<Vec<_>>(),
+          );
+        }
+      }
+    }
+
+    if !class.decorators.is_empty() {
+      if let Some(param_types) = param_types {
+        let param_types = self.serialize_param_types(&param_types);
+        class
+          .decorators
+          .push(create_metadata_decorator("design:paramtypes", param_types));
+      }
+    }
+
+    for member in &mut class.body {
+      match member {
+        ClassMember::Method(method) => {
+          let is_decorated = !method.function.decorators.is_empty()
+            || method
+              .function
+              .params
+              .iter()
+              .any(|param| !param.decorators.is_empty());
+          if is_decorated {
+            let decorators = self.method_metadata(method.kind, &method.function);
+            method.function.decorators.extend(decorators);
+          }
+        }
+        ClassMember::ClassProp(prop) if !prop.decorators.is_empty() => {
+          let type_ann = prop.type_ann.as_ref().map(|type_ann| &*type_ann.type_ann);
+          let design_type = self.serialize_type(type_ann);
+          prop
+            .decorators
+            .push(create_metadata_decorator("design:type", design_type));
+        }
+        _ => {}
+      }
+    }
+
+    self.type_params.truncate(type_params_len);
+  }
+
+  fn method_metadata(&mut self, kind: MethodKind, function: &Function) -> Vec<Decorator> {
+    let type_params_len = self.type_params.len();
+    if let Some(type_params) = &function.type_params {
+      self.type_params.extend(
+        type_params
+          .params
+          .iter()
+          .map(|param| param.name.sym.clone()),
+      );
+    }
+
+    let param_types: Vec<Option<TsType>> = function
+      .params
+      .iter()
+      .filter_map(|param| param_type(&param.pat))
+      .map(|ty| ty.cloned())
+      .collect();
+    let return_type = function
+      .return_type
+      .as_ref()
+      .map(|type_ann| &*type_ann.type_ann);
+
+    let mut decorators = vec![];
+    match kind {
+      MethodKind::Method => {
+        decorators.push(create_metadata_decorator(
+          "design:type",
+          create_ident("Function"),
+        ));
+        let param_types = self.serialize_param_types(&param_types);
+        decorators.push(create_metadata_decorator("design:paramtypes", param_types));
+        let return_type = match return_type {
+          Some(return_type) => self.serialize_type(Some(return_type)),
+          None if function.is_async => create_ident("Promise"),
+          None => create_void_0(),
+        };
+        decorators.push(create_metadata_decorator("design:returntype", return_type));
+      }
+      MethodKind::Getter => {
+        let design_type = self.serialize_type(return_type);
+        decorators.push(create_metadata_decorator("design:type", design_type));
+        decorators.push(create_metadata_decorator(
+          "design:paramtypes",
+          create_array(vec![]),
+        ));
+      }
+      MethodKind::Setter => {
+        let design_type = self.serialize_type(param_types.first().and_then(|ty| ty.as_ref()));
+        decorators.push(create_metadata_decorator("design:type", design_type));
+        let param_types = self.serialize_param_types(&param_types);
+        decorators.push(create_metadata_decorator("design:paramtypes", param_types));
+      }
+    }
+
+    self.type_params.truncate(type_params_len);
+    decorators
+  }
+
+  fn serialize_param_types(&mut self, param_types: &[Option<TsType>]) -> Expr {
+    let elems = param_types
+      .iter()
+      .map(|ty| self.serialize_type(ty.as_ref()))
+      .collect();
+    create_array(elems)
+  }
+
+  fn serialize_type(&mut self, ty: Option<&TsType>) -> Expr {
+    match ty {
+      Some(ty) => self.serialize_type_node(ty),
+      None => create_ident("Object"),
+    }
+  }
+
+  /// Serializes a type node to a runtime value, following TSC's `serializeTypeNode`.
+  fn serialize_type_node(&mut self, ty: &TsType) -> Expr {
+    use TsKeywordTypeKind::*;
+
+    match ty {
+      TsType::TsKeywordType(keyword) => match keyword.kind {
+        TsVoidKeyword | TsUndefinedKeyword | TsNullKeyword | TsNeverKeyword => create_void_0(),
+        TsBooleanKeyword => create_ident("Boolean"),
+        TsStringKeyword => create_ident("String"),
+        TsNumberKeyword => create_ident("Number"),
+        TsBigIntKeyword => create_guarded_reference(create_ident("BigInt")),
+        TsSymbolKeyword => create_ident("Symbol"),
+        _ => create_ident("Object"),
+      },
+      TsType::TsParenthesizedType(ty) => self.serialize_type_node(&ty.type_ann),
+      TsType::TsFnOrConstructorType(_) => create_ident("Function"),
+      TsType::TsArrayType(_) | TsType::TsTupleType(_) => create_ident("Array"),
+      TsType::TsTypePredicate(_) => create_ident("Boolean"),
+      TsType::TsLitType(lit) => match &lit.lit {
+        TsLit::Bool(_) => create_ident("Boolean"),
+        TsLit::Str(_) | TsLit::Tpl(_) => create_ident("String"),
+        TsLit::Number(_) => create_ident("Number"),
+        TsLit::BigInt(_) => create_guarded_reference(create_ident("BigInt")),
+      },
+      TsType::TsTypeOperator(TsTypeOperator {
+        op: TsTypeOperatorOp::ReadOnly,
+        type_ann,
+        ..
+      }) => self.serialize_type_node(type_ann),
+      TsType::TsUnionOrIntersectionType(TsUnionOrIntersectionType::TsUnionType(union)) => {
+        self.serialize_type_list(&union.types)
+      }
+      TsType::TsUnionOrIntersectionType(TsUnionOrIntersectionType::TsIntersectionType(
+        intersection,
+      )) => self.serialize_type_list(&intersection.types),
+      TsType::TsConditionalType(cond) => {
+        self.serialize_type_list(&[cond.true_type.clone(), cond.false_type.clone()])
+      }
+      TsType::TsTypeRef(type_ref) => self.serialize_type_ref(&type_ref.type_name),
+      _ => create_ident("Object"),
+    }
+  }
+
+  /// Serializes a union or intersection. If every member serializes to the same
+  /// constructor, that is used, otherwise `Object`. Like SWC and TSC without
+  /// `strictNullChecks`, `null` and `undefined` members are ignored.
+  fn serialize_type_list(&mut self, types: &[Box<TsType>]) -> Expr {
+    let mut serialized: Option<Expr> = None;
+    for ty in types {
+      let mut ty = &**ty;
+      while let TsType::TsParenthesizedType(parens) = ty {
+        ty = &parens.type_ann;
+      }
+
+      if let TsType::TsKeywordType(keyword) = ty {
+        match keyword.kind {
+          TsKeywordTypeKind::TsNeverKeyword
+          | TsKeywordTypeKind::TsNullKeyword
+          | TsKeywordTypeKind::TsUndefinedKeyword => continue,
+          TsKeywordTypeKind::TsAnyKeyword | TsKeywordTypeKind::TsUnknownKeyword => {
+            return create_ident("Object")
+          }
+          _ => {}
+        }
+      }
+
+      let item = self.serialize_type_node(ty);
+      match (&serialized, &item) {
+        (_, Expr::Ident(ident)) if ident.sym == *"Object" => return item,
+        (None, _) => serialized = Some(item),
+        (Some(Expr::Ident(prev)), Expr::Ident(ident)) if prev.sym == ident.sym => {}
+        _ => return create_ident("Object"),
+      }
+    }
+
+    serialized.unwrap_or_else(create_void_0)
+  }
+
+  fn serialize_type_ref(&mut self, name: &TsEntityName) -> Expr {
+    let ident = match name {
+      TsEntityName::Ident(ident) => ident,
+      TsEntityName::TsQualifiedName(_) => {
+        return create_guarded_reference(entity_name_to_expr(name));
+      }
+    };
+
+    // Type parameters are erased. A reference to the class being decorated would be
+    // in its temporal dead zone when the decorators are evaluated, so use Object too.
+    if self.type_params.contains(&ident.sym) || self.class_name.as_ref() == Some(&ident.sym) {
+      return create_ident("Object");
+    }
+
+    match self.bindings.get(&ident.sym) {
+      Some(Binding::Value) => create_ident(&ident.sym),
+      Some(Binding::Type) => create_ident("Object"),
+      Some(Binding::Enum(kind)) => create_ident(kind),
+      Some(Binding::Alias(ty)) => {
+        if self.resolving.contains(&ident.sym) {
+          return create_ident("Object");
+        }
+        let ty = ty.clone();
+        self.resolving.push(ident.sym.clone());
+        let res = self.serialize_type_node(&ty);
+        self.resolving.pop();
+        res
+      }
+      // Imports, and globals such as `Date` or `HTMLElement`.
+      Some(Binding::Import) | None => create_guarded_reference(create_ident(&ident.sym)),
+    }
+  }
+}
+
+fn enum_kind(decl: &TsEnumDecl) -> &'static str {
+  let mut kind = None;
+  for member in &decl.members {
+    let member_kind = match member.init.as_deref() {
+      None | Some(Expr::Lit(Lit::Num(_))) => "Number",
+      Some(Expr::Lit(Lit::Str(_))) => "String",
+      _ => return "Object",
+    };
+    match kind {
+      None => kind = Some(member_kind),
+      Some(kind) if kind != member_kind => return "Object",
+      _ => {}
+    }
+  }
+  kind.unwrap_or("Number")
+}
+
+fn param_type(pat: &Pat) -> Option<Option<&TsType>> {
+  match pat {
+    // `this` parameters are not emitted.
+    Pat::Ident(ident) if ident.id.sym == *"this" => None,
+    pat => Some(type_of_pat(pat)),
+  }
+}
+
+fn type_of_pat(pat: &Pat) -> Option<&TsType> {
+  let type_ann = match pat {
+    Pat::Ident(ident) => ident.type_ann.as_ref(),
+    Pat::Array(array) => array.type_ann.as_ref(),
+    Pat::Object(object) => object.type_ann.as_ref(),
+    Pat::Assign(assign) => match &assign.type_ann {
+      Some(type_ann) => Some(type_ann),
+      None => return type_of_pat(&assign.left),
+    },
+    // The element type of a rest parameter is emitted, e.g. `String` for `...args: string[]`.
+    Pat::Rest(rest) => {
+      return match rest.type_ann.as_ref().map(|type_ann| &*type_ann.type_ann) {
+        Some(TsType::TsArrayType(array)) => Some(&array.elem_type),
+        _ => None,
+      }
+    }
+    Pat::Invalid(_) | Pat::Expr(_) => None,
+  };
+  type_ann.map(|type_ann| &*type_ann.type_ann)
+}
+
+fn entity_name_to_expr(name: &TsEntityName) -> Expr {
+  match name {
+    TsEntityName::Ident(ident) => create_ident(&ident.sym),
+    TsEntityName::TsQualifiedName(qualified) => Expr::Member(MemberExpr {
+      span: DUMMY_SP,
+      obj: ExprOrSuper::Expr(Box::new(entity_name_to_expr(&qualified.left))),
+      prop: Box::new(create_ident(&qualified.right.sym)),
+      computed: false,
+    }),
+  }
+}
+
+/// Creates `typeof Reflect === "object" && typeof Reflect.metadata === "function"
+///   ? Reflect.metadata(key, value) : function () {}`.
+/// The legacy decorators pass calls every decorator, so a no-op is used when
+/// `reflect-metadata` is not loaded.
+fn create_metadata_decorator(key: &str, value: Expr) -> Decorator {
+  let reflect_metadata = Expr::Member(MemberExpr {
+    span: DUMMY_SP,
+    obj: ExprOrSuper::Expr(Box::new(create_ident("Reflect"))),
+    prop: Box::new(create_ident("metadata")),
+    computed: false,
+  });
+
+  let test = Expr::Bin(BinExpr {
+    span: DUMMY_SP,
+    op: BinaryOp::LogicalAnd,
+    left: Box::new(create_typeof_check(
+      create_ident("Reflect"),
+      BinaryOp::EqEqEq,
+      "object",
+    )),
+    right: Box::new(create_typeof_check(
+      reflect_metadata.clone(),
+      BinaryOp::EqEqEq,
+      "function",
+    )),
+  });
+
+  let call = Expr::Call(CallExpr {
+    span: DUMMY_SP,
+    callee: ExprOrSuper::Expr(Box::new(reflect_metadata)),
+    args: vec![
+      ExprOrSpread {
+        spread: None,
+        expr: Box::new(create_str(key)),
+      },
+      ExprOrSpread {
+        spread: None,
+        expr: Box::new(value),
+      },
+    ],
+    type_args: None,
+  });
+
+  Decorator {
+    span: DUMMY_SP,
+    expr: Box::new(Expr::Cond(CondExpr {
+      span: DUMMY_SP,
+      test: Box::new(test),
+      cons: Box::new(call),
+      alt: Box::new(create_function(vec![], vec![])),
+    })),
+  }
+}
+
+/// Creates `function (target) { return decorator(target, undefined, index) }`.
+fn create_param_decorator(decorator: Decorator, index: usize) -> Decorator {
+  let call = Expr::Call(CallExpr {
+    span: DUMMY_SP,
+    callee: ExprOrSuper::Expr(decorator.expr),
+    args: vec![
+      create_ident("target"),
+      create_ident("undefined"),
+      Expr::Lit(Lit::Num(Number {
+        span: DUMMY_SP,
+        value: index as f64,
+      })),
+    ]
+    .into_iter()
+    .map(|expr| ExprOrSpread {
+      spread: None,
+      expr: Box::new(expr),
+    })
+    .collect(),
+    type_args: None,
+  });
+
+  Decorator {
+    span: decorator.span,
+    expr: Box::new(create_function(
+      vec!["target"],
+      vec![Stmt::Return(ReturnStmt {
+        span: DUMMY_SP,
+        arg: Some(Box::new(call)),
+      })],
+    )),
+  }
+}
+
+/// Creates `typeof value === "undefined" ? Object : value`, so that references
+/// to names that only exist as types at runtime fall back to `Object`.
+fn create_guarded_reference(value: Expr) -> Expr {
+  let mut test = create_typeof_check(value.clone(), BinaryOp::EqEqEq, "undefined");
+  // For `a.b`, check `a` first to avoid a ReferenceError.
+  if let Expr::Member(MemberExpr {
+    obj: ExprOrSuper::Expr(obj),
+    ..
+  }) = &value
+  {
+    let obj = create_guarded_reference(*obj.clone());
+    if let Expr::Cond(CondExpr { test: obj_test, .. }) = obj {
+      test = Expr::Bin(BinExpr {
+        span: DUMMY_SP,
+        op: BinaryOp::LogicalOr,
+        left: obj_test,
+        right: Box::new(test),
+      });
+    }
+  }
+
+  Expr::Cond(CondExpr {
+    span: DUMMY_SP,
+    test: Box::new(test),
+    cons: Box::new(create_ident("Object")),
+    alt: Box::new(value),
+  })
+}
+
+fn create_typeof_check(value: Expr, op: BinaryOp, type_name: &str) -> Expr {
+  Expr::Bin(BinExpr {
+    span: DUMMY_SP,
+    op,
+    left: Box::new(Expr::Unary(UnaryExpr {
+      span: DUMMY_SP,
+      op: UnaryOp::TypeOf,
+      arg: Box::new(value),
+    })),
+    right: Box::new(create_str(type_name)),
+  })
+}
+
+fn create_function(params: Vec<&str>, stmts: Vec<Stmt>) -> Expr {
+  Expr::Fn(FnExpr {
+    ident: None,
+    function: Function {
+      params: params
+        .into_iter()
+        .map(|name| Param {
+          span: DUMMY_SP,
+          decorators: vec![],
+          pat: Pat::Ident(BindingIdent::from(Ident::new(name.into(), DUMMY_SP))),
+        })
+        .collect(),
+      decorators: vec![],
+      span: DUMMY_SP,
+      body: Some(BlockStmt {
+        span: DUMMY_SP,
+        stmts,
+      }),
+      is_generator: false,
+      is_async: false,
+      type_params: None,
+      return_type: None,
+    },
+  })
+}
+
+fn create_array(elems: Vec<Expr>) -> Expr {
+  Expr::Array(ArrayLit {
+    span: DUMMY_SP,
+    elems: elems
+      .into_iter()
+      .map(|expr| {
+        Some(ExprOrSpread {
+          spread: None,
+          expr: Box::new(expr),
+        })
+      })
+      .collect(),
+  })
+}
+
+fn create_ident(name: &str) -> Expr {
+  Expr::Ident(Ident::new(name.into(), DUMMY_SP))
+}
+
+fn create_str(value: &str) -> Expr {
+  Expr::Lit(Lit::Str(Str {
+    span: DUMMY_SP,
+    value: value.into(),
+    has_escape: false,
+    kind: StrKind::Synthesized,
+  }))
+}
+
+fn create_void_0() -> Expr {
+  Expr::Unary(UnaryExpr {
+    span: DUMMY_SP,
+    op: UnaryOp::Void,
+    arg: Box::new(Expr::Lit(Lit::Num(Number {
+      span: DUMMY_SP,
+      value: 0.0,
+    }))),
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use swc_common::comments::SingleThreadedComments;
+  use swc_common::{sync::Lrc, FileName, SourceMap};
+  use swc_ecmascript::codegen::text_writer::JsWriter;
+  use swc_ecmascript::parser::lexer::Lexer;
+  use swc_ecmascript::parser::{Parser, StringInput, Syntax, TsConfig};
+  use swc_ecmascript::visit::{Node, Visit, VisitWith};
+
+  /// Runs the pass and returns each decorator in the output, in order.
+  fn transform(code: &str) -> Vec<String> {
+    let source_map = Lrc::new(SourceMap::default());
+    let source_file = source_map.new_source_file(FileName::Anon, code.into());
+    let comments = SingleThreadedComments::default();
+    let lexer = Lexer::new(
+      Syntax::Typescript(TsConfig {
+        decorators: true,
+        ..Default::default()
+      }),
+      Default::default(),
+      StringInput::from(&*source_file),
+      Some(&comments),
+    );
+    let module = Parser::new_from(lexer).parse_module().unwrap();
+
+    let module = module.fold_with(&mut decorator_metadata());
+
+    let mut collect = CollectDecorators { decorators: vec![] };
+    module.visit_with(&Invalid { span: DUMMY_SP } as _, &mut collect);
+    let decorators = collect
+      .decorators
+      .into_iter()
+      .map(|expr| {
+        let module = Module {
+          span: DUMMY_SP,
+          body: vec![ModuleItem::Stmt(Stmt::Expr(ExprStmt {
+            span: DUMMY_SP,
+            expr: Box::new(expr),
+          }))],
+          shebang: None,
+        };
+        let mut buf = vec![];
+        {
+          let writer = Box::new(JsWriter::new(source_map.clone(), "\n", &mut buf, None));
+          let mut emitter = swc_ecmascript::codegen::Emitter {
+            cfg: swc_ecmascript::codegen::Config { minify: true },
+            comments: None,
+            cm: source_map.clone(),
+            wr: writer,
+          };
+          emitter.emit_module(&module).unwrap();
+        }
+        String::from_utf8(buf).unwrap()
+      })
+      .collect();
+
+    decorators
+  }
+
+  struct CollectDecorators {
+    decorators: Vec<Expr>,
+  }
+
+  impl Visit for CollectDecorators {
+    fn visit_decorator(&mut self, node: &Decorator, _parent: &dyn Node) {
+      self.decorators.push((*node.expr).clone());
+    }
+  }
+
+  /// Returns the key and value of each metadata decorator, e.g. `design:type String`.
+  fn metadata(code: &str) -> Vec<String> {
+    transform(code)
+      .iter()
+      .filter_map(|decorator| {
+        let start = decorator.find("Reflect.metadata(\"")? + 18;
+        let end = decorator.rfind("):function(){}")?;
+        Some(decorator[start..end].replacen("\",", " ", 1))
+      })
+      .collect()
+  }
+  #[test]
+  fn design_type() {
+    assert_eq!(
+      metadata("class Foo { @dec x: string; @dec y: Bar; @dec z: number[]; }\nclass Bar {}"),
+      vec!["design:type String", "design:type Bar", "design:type Array"]
+    );
+    assert_eq!(
+      metadata("enum E { A, B }\ntype T = E | null;\nclass Foo { @dec x: T; @dec get y(): boolean { return true } }"),
+      vec![
+        "design:type Number",
+        "design:type Boolean",
+        "design:paramtypes []"
+      ]
+    );
+  }
+
+  #[test]
+  fn design_paramtypes() {
+    assert_eq!(
+      metadata("@dec class Foo { constructor(a: string, @inject(B) private b: B, c?: Date) {} }\ninterface B {}"),
+      vec!["design:paramtypes [String,Object,typeof Date===\"undefined\"?Object:Date]"]
+    );
+    let decorators = transform("@dec class Foo { constructor(@inject(B) b) {} }");
+    assert_eq!(
+      decorators[1],
+      "function(target){return inject(B)(target,undefined,0);};"
+    );
+  }
+
+  #[test]
+  fn design_returntype() {
+    assert_eq!(
+      metadata(
+        "class Foo { @dec m(this: Foo, a: number, ...rest: string[]): void {} @dec async n() {} }"
+      ),
+      vec![
+        "design:type Function",
+        "design:paramtypes [Number,String]",
+        "design:returntype void 0",
+        "design:type Function",
+        "design:paramtypes []",
+        "design:returntype Promise"
+      ]
+    );
+  }
+
+  #[test]
+  fn imports() {
+    let code = "import {A, B} from './a';\nimport type {D} from './d';\nnew B();\nclass Foo { @dec a: A; @dec b: B; @dec d: D; }";
+    assert_eq!(
+      metadata(code),
+      vec![
+        "design:type typeof A===\"undefined\"?Object:A",
+        "design:type typeof B===\"undefined\"?Object:B",
+        "design:type Object"
+      ]
+    );
+  }
+}
@@ -5,10 +5,11 @@ use std::path::Path;
 
 use serde::{Deserialize, Serialize};
 use swc_atoms::JsWord;
-use swc_common::{Mark, SourceMap, Span, SyntaxContext, DUMMY_SP};
+use swc_common::comments::SingleThreadedComments;
+use swc_common::{Mark, SourceMap, Span, Spanned, SyntaxContext, DUMMY_SP};
 use swc_ecmascript::ast;
 use swc_ecmascript::utils::ident::IdentLike;
-use swc_ecmascript::visit::{Fold, FoldWith};
+use swc_ecmascript::visit::{Fold, FoldWith, Node, Visit, VisitWith};
 
 use crate::utils::*;
 use crate::Config;
@@ -34,6 +35,8 @@ pub enum DependencyKind {
   Worklet,
   Url,
   File,
+  Glob,
+  RequireResolve,
 }
 
 impl fmt::Display for DependencyKind {
@@ -42,17 +45,123 @@ impl fmt::Display for DependencyKind {
   }
 }
 
+/// The environment a module is marked for with a `"use client"` or `"use server"` directive,
+/// e.g. for React Server Components.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Boundary {
+  Client,
+  Server,
+}
+
+impl Boundary {
+  pub fn from_directives(directives: &[JsWord]) -> Option<Self> {
+    directives.iter().find_map(|directive| match &**directive {
+      "use client" => Some(Boundary::Client),
+      "use server" => Some(Boundary::Server),
+      _ => None,
+    })
+  }
+
+  /// Whether a module with this directive is compiled for the other environment, i.e. it is
+  /// the entry of the client or server graph.
+  pub fn is_crossed(self, is_browser: bool) -> bool {
+    match self {
+      Boundary::Client => !is_browser,
+      Boundary::Server => is_browser,
+    }
+  }
+}
+
+/// Describes the module generated for a `DependencyKind::Glob` dependency, whose specifier
+/// is a glob pattern relative to the importer.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum GlobOptions {
+  /// `import.meta.glob(pattern, {eager, import})` returns an object keyed by the relative path
+  /// of each file. The values are functions returning a promise for the module, or the module
+  /// itself if eager. If `import` is set, only that export is returned instead of the namespace.
+  ImportMetaGlob { eager: bool, import: Option<JsWord> },
+  /// `require.context(directory, recursive, regexp, mode)` returns a webpack compatible context
+  /// function, with `keys()`. Files are keyed by their path relative to the directory, and only
+  /// those matching the regexp are included. In lazy mode, the function returns a promise for
+  /// the module, otherwise it also has `resolve()`, returning the id of the module.
+  RequireContext {
+    directory: JsWord,
+    recursive: bool,
+    regexp: JsWord,
+    flags: JsWord,
+    lazy: bool,
+  },
+  /// A partially dynamic `import()` or `require()`, e.g. `` import(`./locales/${lang}.json`) ``,
+  /// is replaced with a call to a function looking up the module by its path relative to the
+  /// importer. It returns a promise for the module if lazy.
+  Lookup { lazy: bool },
+  /// `` new URL(`./flags/${code}.svg`, import.meta.url) `` is replaced with a call to a function
+  /// looking up the URL of the asset by its path relative to the importer.
+  Url,
+}
+
+/// A resource hint for a dynamic import, from a `webpackPrefetch` or `webpackPreload` comment,
+/// or the `prefetch` or `preload` option, e.g. `import('./a', {preload: true})`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum ResourceHint {
+  Prefetch,
+  Preload,
+}
+
+/// How a dynamic import is loaded, from a `webpackMode` comment.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum ImportMode {
+  /// Load the module in a separate bundle. This is the default.
+  Lazy,
+  /// Include the module in the importing bundle, but still return a promise.
+  Eager,
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct DependencyDescriptor {
   pub kind: DependencyKind,
   pub loc: SourceLocation,
   /// The text specifier associated with the import/export statement.
   pub specifier: swc_atoms::JsWord,
-  pub attributes: Option<HashMap<swc_atoms::JsWord, bool>>,
+  /// Import attributes with string values, e.g. `{type: 'json'}` from `assert` on an import or
+  /// export, or the `with` or `assert` option of a dynamic import. The parser does not support
+  /// the `with` keyword on imports and exports yet.
+  pub attributes: Option<HashMap<swc_atoms::JsWord, swc_atoms::JsWord>>,
   pub is_optional: bool,
   pub is_helper: bool,
   pub source_type: Option<SourceType>,
   pub placeholder: Option<String>,
+  /// Set when the dependency is from a `"use client"` module compiled for the server, or a
+  /// `"use server"` module compiled for the browser, so it belongs to that graph instead.
+  pub boundary: Option<Boundary>,
+  pub glob: Option<GlobOptions>,
+  /// Magic comments on a dynamic import, e.g. `import(/* webpackChunkName: "editor" */ './Editor')`.
+  pub chunk_name: Option<String>,
+  pub resource_hint: Option<ResourceHint>,
+  pub import_mode: Option<ImportMode>,
+}
+
+impl DependencyDescriptor {
+  /// Creates a dependency with every optional field unset, to be used with struct update syntax:
+  /// `DependencyDescriptor { is_optional: true, ..DependencyDescriptor::new(kind, loc, specifier) }`
+  pub fn new(kind: DependencyKind, loc: SourceLocation, specifier: swc_atoms::JsWord) -> Self {
+    DependencyDescriptor {
+      kind,
+      loc,
+      specifier,
+      attributes: None,
+      is_optional: false,
+      is_helper: false,
+      source_type: None,
+      placeholder: None,
+      boundary: None,
+      glob: None,
+      chunk_name: None,
+      resource_hint: None,
+      import_mode: None,
+    }
+  }
 }
 
 /// This pass collects dependencies in a module and compiles references as needed to work with Parcel's JSRuntime.
@@ -62,10 +171,12 @@ pub fn dependency_collector<'a>(
   decls: &'a HashSet<(JsWord, SyntaxContext)>,
   ignore_mark: swc_common::Mark,
   config: &'a Config,
+  comments: &'a SingleThreadedComments,
   diagnostics: &'a mut Vec<Diagnostic>,
 ) -> impl Fold + 'a {
   DependencyCollector {
     source_map,
+    comments,
     items,
     in_try: false,
     in_promise: false,
@@ -75,11 +186,15 @@ pub fn dependency_collector<'a>(
     config,
     diagnostics,
     import_meta: None,
+    create_require_bindings: HashSet::new(),
+    preserve_esm: false,
+    boundary: None,
   }
 }
 
 struct DependencyCollector<'a> {
   source_map: &'a SourceMap,
+  comments: &'a SingleThreadedComments,
   items: &'a mut Vec<DependencyDescriptor>,
   in_try: bool,
   in_promise: bool,
@@ -89,15 +204,30 @@ struct DependencyCollector<'a> {
   config: &'a Config,
   diagnostics: &'a mut Vec<Diagnostic>,
   import_meta: Option<ast::VarDecl>,
+  create_require_bindings: HashSet<(JsWord, SyntaxContext)>,
+  /// Whether import and export statements are kept, see `Config::preserve_esm`.
+  preserve_esm: bool,
+  /// Set when the module is the entry of the other graph, see `Boundary::is_crossed`.
+  boundary: Option<Boundary>,
 }
 
 impl<'a> DependencyCollector<'a> {
+  /// Returns the boundary crossed by a dependency. Helpers are inserted for the environment
+  /// the module is compiled for, so they stay in its graph.
+  fn boundary(&self, span: Span) -> Option<Boundary> {
+    if span.is_dummy() {
+      None
+    } else {
+      self.boundary
+    }
+  }
+
   fn add_dependency(
     &mut self,
     specifier: JsWord,
     span: swc_common::Span,
     kind: DependencyKind,
-    attributes: Option<HashMap<swc_atoms::JsWord, bool>>,
+    attributes: Option<HashMap<swc_atoms::JsWord, swc_atoms::JsWord>>,
     is_optional: bool,
     source_type: SourceType,
   ) -> Option<JsWord> {
@@ -105,23 +235,30 @@ impl<'a> DependencyCollector<'a> {
     // For other types of dependencies, the specifier will be changed to a hash
     // that also contains the dependency kind. This way, multiple kinds of dependencies
     // to the same specifier can be used within the same file.
-    let placeholder = match kind {
-      DependencyKind::Import | DependencyKind::Export | DependencyKind::Require => None,
-      _ => Some(format!(
+    // When ES modules are preserved, import/export statements are also rewritten
+    // to a placeholder, which is replaced with the resolved URL during packaging.
+    let needs_placeholder = match kind {
+      DependencyKind::Import | DependencyKind::Export => self.preserve_esm,
+      DependencyKind::Require => false,
+      _ => true,
+    };
+    let placeholder = if needs_placeholder {
+      Some(format!(
         "{:x}",
         hash!(format!("{}:{}:{}", self.config.filename, specifier, kind))
-      )),
+      ))
+    } else {
+      None
     };
 
     self.items.push(DependencyDescriptor {
-      kind,
-      loc: SourceLocation::from(self.source_map, span),
-      specifier,
       attributes,
       is_optional,
       is_helper: span.is_dummy(),
       source_type: Some(source_type),
       placeholder: placeholder.clone(),
+      boundary: self.boundary(span),
+      ..DependencyDescriptor::new(kind, SourceLocation::from(self.source_map, span), specifier)
     });
 
     placeholder.map(|p| p.into())
@@ -157,14 +294,11 @@ impl<'a> DependencyCollector<'a> {
       ))
     );
     self.items.push(DependencyDescriptor {
-      kind,
-      loc: SourceLocation::from(self.source_map, span),
-      specifier,
-      attributes: None,
-      is_optional: false,
       is_helper: span.is_dummy(),
       source_type: Some(source_type),
       placeholder: Some(placeholder.clone()),
+      boundary: self.boundary(span),
+      ..DependencyDescriptor::new(kind, SourceLocation::from(self.source_map, span), specifier)
     });
 
     create_url_constructor(
@@ -178,6 +312,244 @@ impl<'a> DependencyCollector<'a> {
     )
   }
 
+  fn add_glob_dependency(&mut self, pattern: JsWord, span: Span, glob: GlobOptions) -> JsWord {
+    // The options are part of the placeholder, so the same pattern can be used with different options.
+    let placeholder = format!(
+      "{:x}",
+      hash!(format!(
+        "{}:{}:{}:{:?}",
+        self.config.filename,
+        pattern,
+        DependencyKind::Glob,
+        glob
+      ))
+    );
+
+    self.items.push(DependencyDescriptor {
+      source_type: Some(self.config.source_type),
+      placeholder: Some(placeholder.clone()),
+      boundary: self.boundary(span),
+      glob: Some(glob),
+      ..DependencyDescriptor::new(
+        DependencyKind::Glob,
+        SourceLocation::from(self.source_map, span),
+        pattern,
+      )
+    });
+
+    placeholder.into()
+  }
+
+  /// Replaces `import.meta.glob(pattern, options)` with a require of the module generated
+  /// for the glob dependency.
+  fn fold_import_meta_glob(&mut self, node: ast::CallExpr) -> ast::CallExpr {
+    use ast::*;
+
+    let (pattern, span) = match node.args.first() {
+      Some(ExprOrSpread { spread: None, expr }) => match &**expr {
+        Expr::Lit(Lit::Str(str_)) => (str_.value.clone(), str_.span),
+        _ => {
+          self.add_glob_error(
+            expr.span(),
+            "import.meta.glob() only supports a string literal pattern.",
+          );
+          return node;
+        }
+      },
+      _ => {
+        self.add_glob_error(
+          node.span,
+          "import.meta.glob() only supports a string literal pattern.",
+        );
+        return node;
+      }
+    };
+
+    let mut eager = false;
+    let mut import = None;
+    if let Some(arg) = node.args.get(1) {
+      let props = match &*arg.expr {
+        Expr::Object(obj) if arg.spread.is_none() => &obj.props,
+        _ => {
+          self.add_glob_error(
+            arg.expr.span(),
+            "The options of import.meta.glob() must be an object literal.",
+          );
+          return node;
+        }
+      };
+
+      for prop in props {
+        match match_key_value(prop) {
+          Some((key, Expr::Lit(Lit::Bool(Bool { value, .. })))) if &*key == "eager" => {
+            eager = *value
+          }
+          Some((key, Expr::Lit(Lit::Str(Str { value, .. })))) if &*key == "import" => {
+            import = Some(value.clone())
+          }
+          _ => {
+            self.add_glob_error(
+              prop.span(),
+              "Only static `eager` and `import` options are supported by import.meta.glob().",
+            );
+            return node;
+          }
+        }
+      }
+    }
+
+    let placeholder =
+      self.add_glob_dependency(pattern, span, GlobOptions::ImportMetaGlob { eager, import });
+    let mut call = self.create_require(placeholder);
+    call.span = node.span;
+    call
+  }
+
+  /// Replaces `require.context(directory, recursive, regexp, mode)` with a require of the
+  /// module generated for the glob dependency. The defaults match webpack.
+  fn fold_require_context(&mut self, node: ast::CallExpr) -> ast::CallExpr {
+    use ast::*;
+
+    let mut directory = None;
+    let mut recursive = true;
+    let mut regexp: JsWord = "^\\.\\/.*$".into();
+    let mut flags = js_word!("");
+    let mut lazy = false;
+    for (i, arg) in node.args.iter().enumerate() {
+      match (i, &*arg.expr) {
+        (0, Expr::Lit(Lit::Str(str_))) => directory = Some((str_.value.clone(), str_.span)),
+        (1, Expr::Lit(Lit::Bool(Bool { value, .. }))) => recursive = *value,
+        (2, Expr::Lit(Lit::Regex(regex))) => {
+          regexp = regex.exp.clone();
+          flags = regex.flags.clone();
+        }
+        (3, Expr::Lit(Lit::Str(Str { value, .. }))) if value == "sync" || value == "lazy" => {
+          lazy = value == "lazy"
+        }
+        (3, _) => {
+          self.add_glob_error(
+            arg.expr.span(),
+            "Only the \"sync\" and \"lazy\" modes are supported by require.context().",
+          );
+          return node;
+        }
+        _ => {
+          self.add_glob_error(
+            arg.expr.span(),
+            "require.context() only supports static arguments.",
+          );
+          return node;
+        }
+      }
+    }
+
+    let (directory, span) = match directory {
+      Some(directory) => directory,
+      None => {
+        self.add_glob_error(node.span, "require.context() requires a directory.");
+        return node;
+      }
+    };
+
+    let dir = directory.trim_end_matches('/');
+    let pattern = if recursive {
+      format!("{}/**/*", dir)
+    } else {
+      format!("{}/*", dir)
+    };
+    let placeholder = self.add_glob_dependency(
+      pattern.into(),
+      span,
+      GlobOptions::RequireContext {
+        directory,
+        recursive,
+        regexp,
+        flags,
+        lazy,
+      },
+    );
+    let mut call = self.create_require(placeholder);
+    call.span = node.span;
+    call
+  }
+
+  /// Reads the magic comments before an expression, e.g. the specifier of a dynamic import.
+  fn get_magic_comments(&self, span: Span) -> MagicComments {
+    let mut magic_comments = MagicComments::default();
+    self.comments.with_leading(span.lo, |comments| {
+      for comment in comments {
+        match comment.text.trim() {
+          // Vite's ignore comment is supported for compatibility.
+          "@parcel-ignore" | "@vite-ignore" => magic_comments.ignore = true,
+          text => parse_magic_comment(text, &mut magic_comments),
+        }
+      }
+    });
+    magic_comments
+  }
+
+  fn add_create_require_warning(&mut self, ident: &ast::Ident, kind: ast::VarDeclKind) {
+    let kind = match kind {
+      ast::VarDeclKind::Let => "let",
+      _ => "var",
+    };
+    self.diagnostics.push(Diagnostic {
+      message: format!(
+        "Calls to `{}` are not bundled, because it is declared with `{}`.",
+        ident.sym, kind
+      ),
+      code_highlights: Some(vec![CodeHighlight {
+        message: None,
+        loc: SourceLocation::from(self.source_map, ident.span),
+      }]),
+      hints: Some(vec![format!(
+        "Declare `{}` with `const` to bundle the modules it requires.",
+        ident.sym
+      )]),
+      show_environment: false,
+      severity: DiagnosticSeverity::Warning,
+      documentation_url: None,
+    });
+  }
+
+  fn add_glob_specifier_warning(&mut self, span: Span, kind: &DependencyKind) {
+    let call = if *kind == DependencyKind::DynamicImport {
+      "import()"
+    } else {
+      "require()"
+    };
+    self.diagnostics.push(Diagnostic {
+      message: format!(
+        "`{}` with a partially dynamic specifier is not bundled.",
+        call
+      ),
+      code_highlights: Some(vec![CodeHighlight {
+        message: None,
+        loc: SourceLocation::from(self.source_map, span),
+      }]),
+      hints: Some(vec![String::from(
+        "Add `@parcel/resolver-glob` to the resolvers in .parcelrc to bundle the files it could refer to. This is only done for source files.",
+      )]),
+      show_environment: false,
+      severity: DiagnosticSeverity::Warning,
+      documentation_url: None,
+    });
+  }
+
+  fn add_glob_error(&mut self, span: Span, message: &str) {
+    self.diagnostics.push(Diagnostic {
+      message: message.to_string(),
+      code_highlights: Some(vec![CodeHighlight {
+        message: None,
+        loc: SourceLocation::from(self.source_map, span),
+      }]),
+      hints: None,
+      show_environment: false,
+      severity: DiagnosticSeverity::Error,
+      documentation_url: None,
+    });
+  }
+
   fn create_require(&mut self, specifier: JsWord) -> ast::CallExpr {
     let mut res = create_require(specifier);
 
@@ -228,6 +600,18 @@ fn rewrite_require_specifier(node: ast::CallExpr) -> ast::CallExpr {
 
 impl<'a> Fold for DependencyCollector<'a> {
   fn fold_module(&mut self, node: ast::Module) -> ast::Module {
+    // CommonJS modules are converted as usual, since they have no statements to keep.
+    self.preserve_esm =
+      self.config.preserve_esm && !self.config.scope_hoist && has_module_decls(&node);
+    // The dependencies of a "use client" module compiled for the server, or a "use server"
+    // module compiled for the browser, belong to the other graph.
+    self.boundary = Boundary::from_directives(&get_directives(&node))
+      .filter(|boundary| boundary.is_crossed(self.config.is_browser));
+    let (bindings, ignored) = collect_create_require_bindings(&node);
+    self.create_require_bindings = bindings;
+    for (ident, kind) in ignored {
+      self.add_create_require_warning(&ident, kind);
+    }
     let mut res = node.fold_children_with(self);
     if let Some(decl) = self.import_meta.take() {
       res.body.insert(
@@ -258,52 +642,64 @@ impl<'a> Fold for DependencyCollector<'a> {
     node.fold_children_with(self)
   }
 
-  fn fold_import_decl(&mut self, node: ast::ImportDecl) -> ast::ImportDecl {
+  fn fold_import_decl(&mut self, mut node: ast::ImportDecl) -> ast::ImportDecl {
     if node.type_only {
       return node;
     }
 
-    self.add_dependency(
+    let placeholder = self.add_dependency(
       node.src.value.clone(),
       node.src.span,
       DependencyKind::Import,
-      None,
+      node.asserts.as_ref().map(get_import_attributes),
       false,
       self.config.source_type,
     );
 
+    if let Some(placeholder) = placeholder {
+      node.src = create_placeholder_str(placeholder, node.src.span);
+    }
+
     node
   }
 
-  fn fold_named_export(&mut self, node: ast::NamedExport) -> ast::NamedExport {
+  fn fold_named_export(&mut self, mut node: ast::NamedExport) -> ast::NamedExport {
     if let Some(src) = &node.src {
       if node.type_only {
         return node;
       }
 
-      self.add_dependency(
+      let placeholder = self.add_dependency(
         src.value.clone(),
         src.span,
         DependencyKind::Export,
-        None,
+        node.asserts.as_ref().map(get_import_attributes),
         false,
         self.config.source_type,
       );
+
+      if let Some(placeholder) = placeholder {
+        node.src = Some(create_placeholder_str(placeholder, src.span));
+      }
     }
 
     node
   }
 
-  fn fold_export_all(&mut self, node: ast::ExportAll) -> ast::ExportAll {
-    self.add_dependency(
+  fn fold_export_all(&mut self, mut node: ast::ExportAll) -> ast::ExportAll {
+    let placeholder = self.add_dependency(
       node.src.value.clone(),
       node.src.span,
       DependencyKind::Export,
-      None,
+      node.asserts.as_ref().map(get_import_attributes),
       false,
       self.config.source_type,
     );
 
+    if let Some(placeholder) = placeholder {
+      node.src = create_placeholder_str(placeholder, node.src.span);
+    }
+
     node
   }
 
@@ -324,8 +720,8 @@ impl<'a> Fold for DependencyCollector<'a> {
     }
   }
 
-  fn fold_call_expr(&mut self, node: ast::CallExpr) -> ast::CallExpr {
-    use ast::{Expr::*, ExprOrSuper::*, Ident};
+  fn fold_call_expr(&mut self, mut node: ast::CallExpr) -> ast::CallExpr {
+    use ast::{Expr::*, ExprOrSuper::*};
 
     let call_expr = match node.callee.clone() {
       Super(_) => return node,
@@ -333,6 +729,26 @@ impl<'a> Fold for DependencyCollector<'a> {
     };
 
     let kind = match &*call_expr {
+      // const require = createRequire(import.meta.url); require('./data.json')
+      // Calls with a static specifier are bundled like any other require, so the callee
+      // is replaced with the global require. This also makes them regular requires for the
+      // hoist pass, which runs later, so only this pass needs to know about the bindings.
+      // Other calls are left to Node.
+      Ident(ident) if self.create_require_bindings.contains(&ident.to_id()) => {
+        let is_static = matches!(
+          node.args.first(),
+          Some(arg) if matches!(&*arg.expr, Lit(ast::Lit::Str(_))) || match_glob_specifier(&arg.expr).is_some()
+        );
+        if !is_static {
+          return node.fold_children_with(self);
+        }
+
+        node.callee = ast::ExprOrSuper::Expr(Box::new(ast::Expr::Ident(ast::Ident::new(
+          js_word!("require"),
+          DUMMY_SP,
+        ))));
+        DependencyKind::Require
+      }
       Ident(ident) => {
         // Bail if defined in scope
         if self.decls.contains(&ident.to_id()) {
@@ -340,7 +756,21 @@ impl<'a> Fold for DependencyCollector<'a> {
         }
 
         match ident.sym.to_string().as_str() {
-          "import" => DependencyKind::DynamicImport,
+          "import" => {
+            // import(/* @parcel-ignore */ url) is left as a native dynamic import.
+            if let Some(arg) = node.args.first() {
+              if self.get_magic_comments(arg.expr.span()).ignore {
+                let mut call = node.fold_children_with(self);
+                call.callee = ast::ExprOrSuper::Expr(Box::new(ast::Expr::Ident(ast::Ident::new(
+                  "import".into(),
+                  ident.span.apply_mark(self.ignore_mark),
+                ))));
+                return call;
+              }
+            }
+
+            DependencyKind::DynamicImport
+          }
           "require" => {
             if self.in_promise {
               DependencyKind::DynamicImport
@@ -429,6 +859,14 @@ impl<'a> Fold for DependencyCollector<'a> {
         }
       }
       Member(member) => {
+        if self.is_import_meta_prop(member, "glob") {
+          return self.fold_import_meta_glob(node);
+        }
+
+        if match_member_expr(member, vec!["require", "context"], self.decls) {
+          return self.fold_require_context(node);
+        }
+
         if match_member_expr(member, vec!["module", "require"], self.decls) {
           DependencyKind::Require
         } else if self.config.is_browser
@@ -501,35 +939,61 @@ impl<'a> Fold for DependencyCollector<'a> {
       _ => return node.fold_children_with(self),
     };
 
+    // Partially dynamic specifiers become a glob dependency on all files they could refer to.
+    // import(`./locales/${lang}.json`) -> require('./locales/*.json')(`./locales/${lang}.json`)
+    // This needs the glob resolver, so otherwise the call is left as is.
+    if kind == DependencyKind::DynamicImport || kind == DependencyKind::Require {
+      if let Some(arg) = node.args.first() {
+        let pattern = match_glob_specifier(&arg.expr);
+        if pattern.is_some() && !self.config.glob_dependencies {
+          self.add_glob_specifier_warning(arg.expr.span(), &kind);
+        } else if let Some(pattern) = pattern {
+          let placeholder = self.add_glob_dependency(
+            pattern.into(),
+            arg.expr.span(),
+            GlobOptions::Lookup {
+              lazy: kind == DependencyKind::DynamicImport,
+            },
+          );
+          return ast::CallExpr {
+            callee: ast::ExprOrSuper::Expr(Box::new(ast::Expr::Call(
+              self.create_require(placeholder),
+            ))),
+            args: vec![ast::ExprOrSpread {
+              spread: None,
+              expr: arg.expr.clone().fold_with(self),
+            }],
+            span: node.span,
+            type_args: None,
+          };
+        }
+      }
+    }
+
     // Convert import attributes for dynamic import
     let mut attributes = None;
+    let mut resource_hint = None;
     if kind == DependencyKind::DynamicImport {
       if let Some(arg) = node.args.get(1) {
         if let Object(arg) = &*arg.expr {
           let mut attrs = HashMap::new();
-          for key in &arg.props {
-            let prop = match key {
-              ast::PropOrSpread::Prop(prop) => prop,
-              _ => continue,
-            };
-
-            let kv = match &**prop {
-              ast::Prop::KeyValue(kv) => kv,
-              _ => continue,
-            };
-
-            let k = match &kv.key {
-              ast::PropName::Ident(Ident { sym, .. })
-              | ast::PropName::Str(ast::Str { value: sym, .. }) => sym.clone(),
-              _ => continue,
-            };
-
-            let v = match &*kv.value {
-              Lit(ast::Lit::Bool(ast::Bool { value, .. })) => *value,
-              _ => continue,
-            };
-
-            attrs.insert(k, v);
+          for (k, v) in arg.props.iter().filter_map(match_key_value) {
+            match v {
+              // import('./a.json', {with: {type: 'json'}})
+              Object(obj) if &*k == "with" || &*k == "assert" => {
+                attrs.extend(get_import_attributes(obj))
+              }
+              // Bundle hints, e.g. import('./a', {preload: true})
+              Lit(ast::Lit::Bool(ast::Bool { value: true, .. })) if &*k == "preload" => {
+                resource_hint = Some(ResourceHint::Preload)
+              }
+              Lit(ast::Lit::Bool(ast::Bool { value: true, .. }))
+                if &*k == "prefetch" && resource_hint.is_none() =>
+              {
+                resource_hint = Some(ResourceHint::Prefetch)
+              }
+              _ => {}
+            }
           }
 
           attributes = Some(attrs);
@@ -609,30 +1073,42 @@ impl<'a> Fold for DependencyCollector<'a> {
           self.config.source_type,
         );
 
+        // import(/* webpackChunkName: "editor" */ './Editor')
+        if kind == DependencyKind::DynamicImport {
+          let magic_comments = self.get_magic_comments(str_.span);
+          if let Some(dep) = self.items.last_mut() {
+            dep.chunk_name = magic_comments.chunk_name;
+            dep.resource_hint = magic_comments.resource_hint.or(resource_hint);
+            dep.import_mode = magic_comments.import_mode;
+          }
+        }
+
         if let Some(placeholder) = placeholder {
           let mut node = node.clone();
-          node.args[0].expr = Box::new(ast::Expr::Lit(ast::Lit::Str(ast::Str {
-            value: placeholder,
-            span: str_.span,
-            has_escape: false,
-            kind: ast::StrKind::Synthesized,
-          })));
+          node.args[0].expr = Box::new(ast::Expr::Lit(ast::Lit::Str(create_placeholder_str(
+            placeholder,
+            str_.span,
+          ))));
           node
         } else {
           node
         }
       } else {
+        // The specifier can still contain dependencies, e.g. require(require.resolve('./a')).
+        let mut node = node;
+        node.args = node.args.fold_with(self);
         node
       }
     } else {
       node
     };
 
-    // Replace import() with require()
+    // Replace import() with require(), unless ES modules are preserved.
     if kind == DependencyKind::DynamicImport {
       let mut call = node;
       if !self.config.scope_hoist {
         let name = match &self.config.source_type {
+          SourceType::Module if self.preserve_esm => "import",
           SourceType::Module => "require",
           SourceType::Script => "__parcel__require__",
         };
@@ -646,7 +1122,11 @@ impl<'a> Fold for DependencyCollector<'a> {
       call.args.truncate(1);
 
       // Track the returned require call to be replaced with a promise chain.
-      let rewritten_call = rewrite_require_specifier(call);
+      let rewritten_call = if self.preserve_esm {
+        call
+      } else {
+        rewrite_require_specifier(call)
+      };
       self.require_node = Some(rewritten_call.clone());
       rewritten_call
     } else if kind == DependencyKind::Require {
@@ -794,6 +1274,33 @@ impl<'a> Fold for DependencyCollector<'a> {
   fn fold_expr(&mut self, node: ast::Expr) -> ast::Expr {
     use ast::*;
 
+    // import.meta.resolve('./worker.js') -> the URL of the bundled asset, like new URL('./worker.js', import.meta.url).href
+    if let Some((specifier, span)) = self.match_import_meta_resolve(&node) {
+      // Node supports import.meta.resolve natively, so keep it in ES module libraries.
+      if self.config.is_library && !self.config.is_browser && self.config.is_esm_output {
+        return node;
+      }
+
+      let url = self.add_url_dependency(
+        specifier,
+        span,
+        DependencyKind::Url,
+        self.config.source_type,
+      );
+
+      // Libraries get a URL object, but import.meta.resolve returns a string.
+      if self.config.is_library {
+        return Expr::Member(MemberExpr {
+          span: DUMMY_SP,
+          obj: ExprOrSuper::Expr(Box::new(url)),
+          prop: Box::new(Expr::Ident(Ident::new("href".into(), DUMMY_SP))),
+          computed: false,
+        });
+      }
+
+      return url;
+    }
+
     if self.is_import_meta(&node) {
       return self.get_import_meta();
     }
@@ -802,6 +1309,22 @@ impl<'a> Fold for DependencyCollector<'a> {
       return self.get_import_meta_url();
     }
 
+    // new URL(`./flags/${code}.svg`, import.meta.url) -> require('./flags/*.svg')(`./flags/${code}.svg`)
+    if let Some((pattern, specifier)) = self.match_new_url_glob(&node) {
+      let span = specifier.span();
+      let specifier = specifier.fold_with(self);
+      let placeholder = self.add_glob_dependency(pattern.into(), span, GlobOptions::Url);
+      return Expr::Call(CallExpr {
+        callee: ExprOrSuper::Expr(Box::new(Expr::Call(self.create_require(placeholder)))),
+        args: vec![ExprOrSpread {
+          spread: None,
+          expr: specifier,
+        }],
+        span: DUMMY_SP,
+        type_args: None,
+      });
+    }
+
     if let Some((specifier, span)) = self.match_new_url(&node, self.decls) {
       let url = self.add_url_dependency(
         specifier,
@@ -827,6 +1350,31 @@ impl<'a> Fold for DependencyCollector<'a> {
       return url;
     }
 
+    // require.resolve('./worker.js') -> the id of the module, which is bundled like other
+    // requires, but only executed if required by that id. The placeholder is replaced with
+    // the id when packaging.
+    if let Some((specifier, span, is_paths)) = self.match_require_resolve(&node) {
+      // require.resolve.paths() returns the directories Node would search for the module at
+      // runtime, which don't depend on the bundle. The call is left untouched, and `require`
+      // is kept rather than replaced with undefined, so that it still works in Node.
+      if is_paths {
+        return node;
+      }
+
+      let placeholder = self.add_dependency(
+        specifier.clone(),
+        span,
+        DependencyKind::RequireResolve,
+        None,
+        false,
+        self.config.source_type,
+      );
+      return Expr::Lit(Lit::Str(create_placeholder_str(
+        placeholder.unwrap_or(specifier),
+        span,
+      )));
+    }
+
     let is_require = match &node {
       Expr::Ident(Ident { sym, span, .. }) => {
         // Free `require` -> undefined
@@ -1022,6 +1570,175 @@ fn build_promise_chain(node: ast::CallExpr, require_node: ast::CallExpr) -> ast:
   node
 }
 
+#[derive(Default)]
+struct MagicComments {
+  chunk_name: Option<String>,
+  resource_hint: Option<ResourceHint>,
+  import_mode: Option<ImportMode>,
+  ignore: bool,
+}
+
+/// Parses a webpack magic comment, which contains comma separated `key: value` pairs,
+/// e.g. `webpackChunkName: "editor", webpackPrefetch: true`. Unknown keys are ignored.
+fn parse_magic_comment(text: &str, magic_comments: &mut MagicComments) {
+  for entry in split_magic_comment(text) {
+    let (key, value) = match entry.split_once(':') {
+      Some((key, value)) => (key.trim(), value.trim()),
+      None => continue,
+    };
+
+    let string_value = value
+      .strip_prefix('"')
+      .and_then(|v| v.strip_suffix('"'))
+      .or_else(|| value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')));
+    // Numbers are prefetch/preload orders in webpack, which are also enabled unless zero.
+    let is_enabled = value == "true" || matches!(value.parse::<f64>(), Ok(n) if n != 0.0);
+
+    match key {
+      "webpackChunkName" if string_value.is_some() => {
+        magic_comments.chunk_name = string_value.map(|name| name.to_string())
+      }
+      // Preload takes precedence over prefetch if both are set.
+      "webpackPreload" if is_enabled => magic_comments.resource_hint = Some(ResourceHint::Preload),
+      "webpackPrefetch" if is_enabled && magic_comments.resource_hint.is_none() => {
+        magic_comments.resource_hint = Some(ResourceHint::Prefetch)
+      }
+      "webpackIgnore" => magic_comments.ignore = value == "true",
+      "webpackMode" => match string_value {
+        Some("lazy") | Some("lazy-once") => magic_comments.import_mode = Some(ImportMode::Lazy),
+        Some("eager") => magic_comments.import_mode = Some(ImportMode::Eager),
+        _ => {}
+      },
+      _ => {}
+    }
+  }
+}
+
+/// Splits a magic comment on commas, except those in quoted strings.
+fn split_magic_comment(text: &str) -> Vec<&str> {
+  let mut entries = vec![];
+  let mut start = 0;
+  let mut quote = None;
+  for (i, c) in text.char_indices() {
+    match (c, quote) {
+      ('"', None) | ('\'', None) => quote = Some(c),
+      (c, Some(q)) if c == q => quote = None,
+      (',', None) => {
+        entries.push(&text[start..i]);
+        start = i + 1;
+      }
+      _ => {}
+    }
+  }
+  entries.push(&text[start..]);
+  entries
+}
+
+/// Matches a partially dynamic specifier, e.g. `` `./locales/${lang}.json` `` or
+/// `'./icons/' + name`, and returns a glob pattern with a `*` for each dynamic part.
+/// The specifier must start with a relative path, and the static parts can't contain
+/// glob syntax.
+fn match_glob_specifier(expr: &ast::Expr) -> Option<String> {
+  let mut parts = vec![];
+  collect_specifier_parts(expr, &mut parts);
+
+  let mut pattern = String::new();
+  let mut is_dynamic = false;
+  for part in parts {
+    match part {
+      Some(part) => {
+        if part.contains(&['*', '?', '[', ']', '{', '}', '(', ')', '!'][..]) {
+          return None;
+        }
+        pattern.push_str(&part);
+      }
+      None => {
+        if !pattern.ends_with('*') {
+          pattern.push('*');
+        }
+        is_dynamic = true;
+      }
+    }
+  }
+
+  if is_dynamic && (pattern.starts_with("./") || pattern.starts_with("../")) {
+    Some(pattern)
+  } else {
+    None
+  }
+}
+
+/// Splits a template literal or string concatenation into its static parts, and `None`
+/// for each dynamic part.
+fn collect_specifier_parts(expr: &ast::Expr, parts: &mut Vec<Option<JsWord>>) {
+  use ast::*;
+
+  match expr {
+    Expr::Lit(Lit::Str(str_)) => parts.push(Some(str_.value.clone())),
+    Expr::Tpl(tpl) => {
+      for (i, quasi) in tpl.quasis.iter().enumerate() {
+        let value = quasi.cooked.as_ref().unwrap_or(&quasi.raw);
+        parts.push(Some(value.value.clone()));
+        if let Some(expr) = tpl.exprs.get(i) {
+          collect_specifier_parts(expr, parts);
+        }
+      }
+    }
+    Expr::Bin(BinExpr {
+      op: BinaryOp::Add,
+      left,
+      right,
+      ..
+    }) => {
+      collect_specifier_parts(left, parts);
+      collect_specifier_parts(right, parts);
+    }
+    Expr::Paren(ParenExpr { expr, .. }) => collect_specifier_parts(expr, parts),
+    _ => parts.push(None),
+  }
+}
+
+/// Returns the key and value of a `key: value` property with a static key.
+fn match_key_value(prop: &ast::PropOrSpread) -> Option<(JsWord, &ast::Expr)> {
+  let kv = match prop {
+    ast::PropOrSpread::Prop(prop) => match &**prop {
+      ast::Prop::KeyValue(kv) => kv,
+      _ => return None,
+    },
+    _ => return None,
+  };
+
+  let key = match &kv.key {
+    ast::PropName::Ident(ast::Ident { sym, .. })
+    | ast::PropName::Str(ast::Str { value: sym, .. }) => sym.clone(),
+    _ => return None,
+  };
+
+  Some((key, &*kv.value))
+}
+
+/// Collects the string values of an import attributes object, e.g. `{type: 'json'}`.
+fn get_import_attributes(obj: &ast::ObjectLit) -> HashMap<JsWord, JsWord> {
+  obj
+    .props
+    .iter()
+    .filter_map(match_key_value)
+    .filter_map(|(k, v)| match v {
+      ast::Expr::Lit(ast::Lit::Str(ast::Str { value, .. })) => Some((k, value.clone())),
+      _ => None,
+    })
+    .collect()
+}
+
+fn create_placeholder_str(placeholder: JsWord, span: Span) -> ast::Str {
+  ast::Str {
+    value: placeholder,
+    span,
+    has_escape: false,
+    kind: ast::StrKind::Synthesized,
+  }
+}
+
 fn create_url_constructor(url: ast::Expr, use_import_meta: bool) -> ast::Expr {
   use ast::*;
 
@@ -1067,6 +1784,136 @@ fn create_url_constructor(url: ast::Expr, use_import_meta: bool) -> ast::Expr {
   })
 }
 
+type CreateRequireBindings = (
+  HashSet<(JsWord, SyntaxContext)>,
+  Vec<(ast::Ident, ast::VarDeclKind)>,
+);
+
+/// Collects the bindings created by `createRequire(import.meta.url)` in ES modules, where
+/// `createRequire` is imported from Node's `module` builtin. Calls through these bindings
+/// are handled like calls to the global `require`. Also returns the `let` and `var` bindings,
+/// which are ignored because they could be reassigned.
+fn collect_create_require_bindings(module: &ast::Module) -> CreateRequireBindings {
+  let mut c = CreateRequireCollector {
+    create_require: HashSet::new(),
+    namespaces: HashSet::new(),
+    bindings: HashSet::new(),
+    ignored: vec![],
+  };
+
+  // Imports are hoisted, so find them before looking at any declarations.
+  for item in &module.body {
+    if let ast::ModuleItem::ModuleDecl(ast::ModuleDecl::Import(import)) = item {
+      c.add_import(import);
+    }
+  }
+
+  if !c.create_require.is_empty() || !c.namespaces.is_empty() {
+    module.visit_with(&ast::Invalid { span: DUMMY_SP } as _, &mut c);
+  }
+  (c.bindings, c.ignored)
+}
+
+struct CreateRequireCollector {
+  /// import {createRequire} from 'module';
+  create_require: HashSet<(JsWord, SyntaxContext)>,
+  /// import module from 'module'; OR import * as module from 'module';
+  namespaces: HashSet<(JsWord, SyntaxContext)>,
+  bindings: HashSet<(JsWord, SyntaxContext)>,
+  ignored: Vec<(ast::Ident, ast::VarDeclKind)>,
+}
+
+impl CreateRequireCollector {
+  fn add_import(&mut self, import: &ast::ImportDecl) {
+    if import.type_only || (&*import.src.value != "module" && &*import.src.value != "node:module") {
+      return;
+    }
+
+    for specifier in &import.specifiers {
+      match specifier {
+        ast::ImportSpecifier::Named(named) => {
+          let imported = match &named.imported {
+            Some(imported) => &imported.sym,
+            None => &named.local.sym,
+          };
+          if &**imported == "createRequire" {
+            self.create_require.insert(named.local.to_id());
+          }
+        }
+        ast::ImportSpecifier::Default(default) => {
+          self.namespaces.insert(default.local.to_id());
+        }
+        ast::ImportSpecifier::Namespace(namespace) => {
+          self.namespaces.insert(namespace.local.to_id());
+        }
+      }
+    }
+  }
+
+  fn is_create_require(&self, expr: &ast::Expr) -> bool {
+    use ast::*;
+
+    let call = match expr {
+      Expr::Call(call) => call,
+      _ => return false,
+    };
+
+    let is_callee = match &call.callee {
+      ExprOrSuper::Expr(callee) => match &**callee {
+        Expr::Ident(ident) => self.create_require.contains(&ident.to_id()),
+        Expr::Member(MemberExpr {
+          obj: ExprOrSuper::Expr(obj),
+          prop,
+          computed: false,
+          ..
+        }) => match (&**obj, &**prop) {
+          (Expr::Ident(obj), Expr::Ident(prop)) => {
+            self.namespaces.contains(&obj.to_id()) && &*prop.sym == "createRequire"
+          }
+          _ => false,
+        },
+        _ => false,
+      },
+      _ => false,
+    };
+
+    // Only import.meta.url is supported, as the specifiers are resolved relative to this module.
+    is_callee
+      && match call.args.first().map(|arg| &*arg.expr) {
+        Some(Expr::Member(MemberExpr {
+          obj: ExprOrSuper::Expr(obj),
+          prop,
+          computed: false,
+          ..
+        })) => {
+          matches!(&**obj, Expr::MetaProp(MetaPropExpr { meta, prop: meta_prop })
+            if meta.sym == js_word!("import") && &*meta_prop.sym == "meta")
+            && matches!(&**prop, Expr::Ident(Ident { sym, .. }) if sym == &js_word!("url"))
+        }
+        _ => false,
+      }
+  }
+}
+
+impl Visit for CreateRequireCollector {
+  fn visit_var_decl(&mut self, node: &ast::VarDecl, _parent: &dyn Node) {
+    // Only constant bindings, so every call through them is known to be a require.
+    for decl in &node.decls {
+      if let (ast::Pat::Ident(name), Some(init)) = (&decl.name, &decl.init) {
+        if self.is_create_require(init) {
+          if node.kind == ast::VarDeclKind::Const {
+            self.bindings.insert(name.id.to_id());
+          } else {
+            self.ignored.push((name.id.clone(), node.kind));
+          }
+        }
+      }
+    }
+
+    node.visit_children_with(self);
+  }
+}
+
 struct PromiseTransformer {
   require_node: Option<ast::CallExpr>,
 }
@@ -1168,6 +2015,35 @@ impl<'a> DependencyCollector<'a> {
     None
   }
 
+  /// Matches `new URL()` with a partially dynamic path relative to `import.meta.url`, and
+  /// returns the glob pattern and the path.
+  fn match_new_url_glob(&mut self, expr: &ast::Expr) -> Option<(String, Box<ast::Expr>)> {
+    use ast::*;
+
+    let new = match expr {
+      Expr::New(new) => new,
+      _ => return None,
+    };
+
+    match &*new.callee {
+      Expr::Ident(id) if id.sym == js_word!("URL") && !self.decls.contains(&id.to_id()) => {}
+      _ => return None,
+    }
+
+    let args = new.args.as_ref()?;
+    let (specifier, base) = match (args.first(), args.get(1)) {
+      (Some(specifier), Some(base)) => (specifier, base),
+      _ => return None,
+    };
+
+    let pattern = match_glob_specifier(&specifier.expr)?;
+    if self.is_import_meta_url(&base.expr) {
+      Some((pattern, specifier.expr.clone()))
+    } else {
+      None
+    }
+  }
+
   fn is_import_meta_url(&mut self, expr: &ast::Expr) -> bool {
     use ast::*;
 
@@ -1202,6 +2078,106 @@ impl<'a> DependencyCollector<'a> {
     }
   }
 
+  /// Matches `import.meta.resolve()` with a string literal specifier.
+  fn match_import_meta_resolve(&mut self, expr: &ast::Expr) -> Option<(JsWord, Span)> {
+    use ast::*;
+
+    let call = match expr {
+      Expr::Call(call) => call,
+      _ => return None,
+    };
+
+    match &call.callee {
+      ExprOrSuper::Expr(callee) => match &**callee {
+        Expr::Member(member) if self.is_import_meta_prop(member, "resolve") => {}
+        _ => return None,
+      },
+      _ => return None,
+    }
+
+    match call.args.first() {
+      Some(ExprOrSpread { spread: None, expr }) => match &**expr {
+        Expr::Lit(Lit::Str(str_)) => Some((str_.value.clone(), str_.span)),
+        _ => None,
+      },
+      _ => None,
+    }
+  }
+
+  /// Matches `require.resolve()` and `require.resolve.paths()` with a string literal specifier.
+  /// The boolean is true for `require.resolve.paths()`.
+  fn match_require_resolve(&self, expr: &ast::Expr) -> Option<(JsWord, Span, bool)> {
+    use ast::*;
+
+    let call = match expr {
+      Expr::Call(call) => call,
+      _ => return None,
+    };
+
+    let is_paths = match &call.callee {
+      ExprOrSuper::Expr(callee) => match &**callee {
+        Expr::Member(member) if self.is_require_resolve(member) => false,
+        Expr::Member(MemberExpr {
+          obj: ExprOrSuper::Expr(obj),
+          prop,
+          computed: false,
+          ..
+        }) => match (&**obj, &**prop) {
+          (Expr::Member(member), Expr::Ident(prop))
+            if &*prop.sym == "paths" && self.is_require_resolve(member) =>
+          {
+            true
+          }
+          _ => return None,
+        },
+        _ => return None,
+      },
+      _ => return None,
+    };
+
+    match call.args.first() {
+      Some(ExprOrSpread { spread: None, expr }) => match &**expr {
+        Expr::Lit(Lit::Str(str_)) => Some((str_.value.clone(), str_.span, is_paths)),
+        _ => None,
+      },
+      _ => None,
+    }
+  }
+
+  /// Matches `require.resolve` on the global require, or a binding created by `createRequire()`.
+  fn is_require_resolve(&self, member: &ast::MemberExpr) -> bool {
+    use ast::*;
+
+    if match_member_expr(member, vec!["require", "resolve"], self.decls) {
+      return true;
+    }
+
+    match (&member.obj, &*member.prop) {
+      (ExprOrSuper::Expr(obj), Expr::Ident(prop))
+        if !member.computed && &*prop.sym == "resolve" =>
+      {
+        matches!(&**obj, Expr::Ident(ident) if self.create_require_bindings.contains(&ident.to_id()))
+      }
+      _ => false,
+    }
+  }
+
+  /// Matches `import.meta.<prop>`, e.g. the callee of `import.meta.glob()`.
+  fn is_import_meta_prop(&mut self, member: &ast::MemberExpr, prop: &str) -> bool {
+    use ast::*;
+
+    match &member.obj {
+      ExprOrSuper::Expr(expr) if self.is_import_meta(expr) => {}
+      _ => return false,
+    }
+
+    match &*member.prop {
+      Expr::Ident(id) => &*id.sym == prop && !member.computed,
+      Expr::Lit(Lit::Str(str)) => &*str.value == prop,
+      _ => false,
+    }
+  }
+
   fn is_import_meta(&mut self, expr: &ast::Expr) -> bool {
     use ast::*;
 
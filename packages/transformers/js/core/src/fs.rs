@@ -182,16 +182,11 @@ impl<'a> InlineFS<'a> {
         }));
 
         // Add a file dependency so the cache is invalidated when this file changes.
-        self.deps.push(DependencyDescriptor {
-          kind: DependencyKind::File,
-          loc: SourceLocation::from(&self.collect.source_map, span),
-          specifier: path.to_str().unwrap().into(),
-          attributes: None,
-          is_optional: false,
-          is_helper: false,
-          source_type: None,
-          placeholder: None,
-        });
+        self.deps.push(DependencyDescriptor::new(
+          DependencyKind::File,
+          SourceLocation::from(&self.collect.source_map, span),
+          path.to_str().unwrap().into(),
+        ));
 
         // If buffer, wrap in Buffer.from(base64String, 'base64')
         if encoding == "buffer" {
@@ -69,14 +69,12 @@ impl<'a> Fold for GlobalReplacer<'a> {
 
           let specifier = id.sym.clone();
           self.items.push(DependencyDescriptor {
-            kind: DependencyKind::Require,
-            loc: SourceLocation::from(self.source_map, id.span),
-            specifier,
-            attributes: None,
-            is_optional: false,
-            is_helper: false,
             source_type: Some(SourceType::Module),
-            placeholder: None,
+            ..DependencyDescriptor::new(
+              DependencyKind::Require,
+              SourceLocation::from(self.source_map, id.span),
+              specifier,
+            )
           });
         }
         "Buffer" => {
@@ -98,14 +96,12 @@ impl<'a> Fold for GlobalReplacer<'a> {
           self.decls.insert(id.to_id());
 
           self.items.push(DependencyDescriptor {
-            kind: DependencyKind::Require,
-            loc: SourceLocation::from(self.source_map, id.span),
-            specifier,
-            attributes: None,
-            is_optional: false,
-            is_helper: false,
             source_type: Some(SourceType::Module),
-            placeholder: None,
+            ..DependencyDescriptor::new(
+              DependencyKind::Require,
+              SourceLocation::from(self.source_map, id.span),
+              specifier,
+            )
           });
         }
         "__filename" => {
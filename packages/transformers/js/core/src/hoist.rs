@@ -154,7 +154,7 @@ impl<'a> Fold for Hoist<'a> {
             ModuleDecl::Import(import) => {
               hoisted_imports.push(ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
                 specifiers: vec![],
-                asserts: None,
+                asserts: import.asserts.clone(),
                 span: DUMMY_SP,
                 src: Str {
                   value: format!("{}:{}", self.module_id, import.src.value).into(),
@@ -203,7 +203,7 @@ impl<'a> Fold for Hoist<'a> {
                 // TODO: skip if already imported.
                 hoisted_imports.push(ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
                   specifiers: vec![],
-                  asserts: None,
+                  asserts: export.asserts.clone(),
                   span: DUMMY_SP,
                   src: Str {
                     value: format!("{}:{}", self.module_id, src.value).into(),
@@ -287,7 +287,7 @@ impl<'a> Fold for Hoist<'a> {
             ModuleDecl::ExportAll(export) => {
               hoisted_imports.push(ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
                 specifiers: vec![],
-                asserts: None,
+                asserts: export.asserts.clone(),
                 span: DUMMY_SP,
                 src: Str {
                   value: format!("{}:{}", self.module_id, export.src.value).into(),
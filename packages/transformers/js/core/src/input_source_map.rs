@@ -0,0 +1,325 @@
+use std::path::{Component, Path, PathBuf};
+
+use data_encoding::BASE64;
+use path_slash::PathBufExt;
+use swc_common::comments::SingleThreadedComments;
+use swc_common::{SourceMap, Span};
+
+use crate::dependency_collector::{DependencyDescriptor, DependencyKind};
+use crate::utils::{CodeHighlight, Diagnostic, DiagnosticSeverity, SourceLocation};
+use crate::Config;
+
+/// Loads the source map of the input code, so that the output source map can be composed
+/// through to the original sources. The map is either passed explicitly in the config, or
+/// referenced by a `//# sourceMappingURL` comment when `detect_source_map_url` is enabled.
+/// In the latter case, the comment is removed since it no longer applies to the output.
+pub fn load_input_source_map(
+  config: &Config,
+  source_map: &SourceMap,
+  comments: &SingleThreadedComments,
+  deps: &mut Vec<DependencyDescriptor>,
+) -> Result<Option<sourcemap::SourceMap>, Diagnostic> {
+  let filename = Path::new(&config.filename);
+  let dir = filename.parent().unwrap_or_else(|| Path::new(""));
+  let url = if config.detect_source_map_url {
+    take_source_map_url(comments)
+  } else {
+    None
+  };
+
+  let (contents, map_dir, span) = if let Some(map) = &config.input_source_map {
+    (map.as_bytes().to_vec(), dir.to_path_buf(), None)
+  } else if let Some((url, span)) = url {
+    if let Some(data) = url.strip_prefix("data:") {
+      // e.g. data:application/json;charset=utf-8;base64,...
+      let contents = match data.find(";base64,") {
+        Some(index) => BASE64.decode(&data.as_bytes()[index + 8..]).ok(),
+        None => data
+          .find(',')
+          .and_then(|index| percent_decode(&data[index + 1..])),
+      };
+      match contents {
+        Some(contents) => (contents, dir.to_path_buf(), Some(span)),
+        None => {
+          return Err(source_map_warning(
+            "Invalid inline source map data URL.".into(),
+            source_map,
+            span,
+          ))
+        }
+      }
+    } else if url.contains("://") {
+      // Remote source maps are not loaded.
+      return Ok(None);
+    } else {
+      let path = dir.join(&url);
+      match std::fs::read(&path) {
+        Ok(contents) => {
+          // Add a file dependency so the cache is invalidated when the map changes.
+          deps.push(DependencyDescriptor::new(
+            DependencyKind::File,
+            SourceLocation::from(source_map, span),
+            path.to_string_lossy().into(),
+          ));
+          let map_dir = path.parent().unwrap_or(dir).to_path_buf();
+          (contents, map_dir, Some(span))
+        }
+        Err(err) => {
+          return Err(source_map_warning(
+            format!("Could not read source map {}: {}", url, err),
+            source_map,
+            span,
+          ))
+        }
+      }
+    }
+  } else {
+    return Ok(None);
+  };
+
+  let decoded = sourcemap::decode_slice(&contents).and_then(|map| match map {
+    sourcemap::DecodedMap::Regular(map) => Ok(map),
+    sourcemap::DecodedMap::Index(index) => index.flatten(),
+    sourcemap::DecodedMap::Hermes(hermes) => Ok((*hermes).clone()),
+  });
+
+  let mut map = match decoded {
+    Ok(map) => map,
+    Err(err) => {
+      let message = format!("Could not parse input source map: {}", err);
+      return Err(match span {
+        Some(span) => source_map_warning(message, source_map, span),
+        None => Diagnostic {
+          message,
+          code_highlights: None,
+          hints: None,
+          show_environment: false,
+          severity: DiagnosticSeverity::Warning,
+          documentation_url: None,
+        },
+      });
+    }
+  };
+
+  // Sources are relative to the source map, or to its `sourceRoot`, which the decoder has
+  // already prepended. Make them relative to the project root instead, matching the source
+  // names of the generated map.
+  for i in 0..map.get_source_count() {
+    let source = match map.get_source(i) {
+      Some(source) if !source.contains("://") => source.to_string(),
+      _ => continue,
+    };
+
+    let path = normalize_path(&map_dir.join(&source));
+    let path = if let Ok(relative) = path.strip_prefix(&config.project_root) {
+      relative.to_path_buf()
+    } else {
+      path
+    };
+    map.set_source(i, &path.to_slash_lossy());
+  }
+
+  Ok(Some(map))
+}
+
+/// Composes a generated source map with the input source map, so that each mapping
+/// points to the original source rather than the intermediate code.
+pub fn compose_source_maps(
+  map: &sourcemap::SourceMap,
+  input: &sourcemap::SourceMap,
+) -> sourcemap::SourceMap {
+  // The first input token on each line, used when a token precedes every mapping on its line.
+  let mut first_tokens = Vec::new();
+  for (index, token) in input.tokens().enumerate() {
+    let line = token.get_dst_line() as usize;
+    if first_tokens.len() <= line {
+      first_tokens.resize(line + 1, None);
+    }
+    if first_tokens[line].is_none() {
+      first_tokens[line] = Some(index as u32);
+    }
+  }
+
+  let mut builder = sourcemap::SourceMapBuilder::new(None);
+  for token in map.tokens() {
+    let line = token.get_src_line();
+    let original = match input.lookup_token(line, token.get_src_col()) {
+      Some(original) if original.get_dst_line() == line => original,
+      // The closest mapping is on an earlier line. Prefer the first mapping on the same line,
+      // otherwise the line is a continuation of the earlier mapping (e.g. a multi-line string).
+      closest => match first_tokens.get(line as usize).copied().flatten() {
+        Some(index) => input.get_token(index).unwrap(),
+        None => match closest {
+          Some(original) => original,
+          None => continue,
+        },
+      },
+    };
+
+    let raw = builder.add(
+      token.get_dst_line(),
+      token.get_dst_col(),
+      original.get_src_line(),
+      original.get_src_col(),
+      original.get_source(),
+      original.get_name().or_else(|| token.get_name()),
+    );
+
+    if raw.src_id != !0 && !builder.has_source_contents(raw.src_id) {
+      let contents = input.get_source_contents(original.get_src_id());
+      builder.set_source_contents(raw.src_id, contents);
+    }
+  }
+
+  builder.into_sourcemap()
+}
+
+/// Finds the last `sourceMappingURL` comment in the module, removes it, and returns its url.
+fn take_source_map_url(comments: &SingleThreadedComments) -> Option<(String, Span)> {
+  let mut found: Option<(String, Span)> = None;
+  {
+    let (leading, trailing) = comments.borrow_all();
+    for comment in leading.values().chain(trailing.values()).flatten() {
+      let text = comment.text.trim();
+      let url = match text
+        .strip_prefix("# sourceMappingURL=")
+        .or_else(|| text.strip_prefix("@ sourceMappingURL="))
+      {
+        Some(url) => url.trim(),
+        None => continue,
+      };
+
+      match &found {
+        Some((_, span)) if span.lo > comment.span.lo => {}
+        _ => found = Some((url.to_string(), comment.span)),
+      }
+    }
+  }
+
+  if let Some((_, span)) = &found {
+    let (leading, trailing) = comments.clone().take_all();
+    for map in &[leading, trailing] {
+      for list in map.borrow_mut().values_mut() {
+        list.retain(|comment| comment.span != *span);
+      }
+    }
+  }
+
+  found
+}
+
+/// Decodes the `%XX` escapes of a non-base64 data URL.
+fn percent_decode(data: &str) -> Option<Vec<u8>> {
+  let bytes = data.as_bytes();
+  let mut res = Vec::with_capacity(bytes.len());
+  let mut i = 0;
+  while i < bytes.len() {
+    if bytes[i] == b'%' {
+      let hex = std::str::from_utf8(bytes.get(i + 1..i + 3)?).ok()?;
+      res.push(u8::from_str_radix(hex, 16).ok()?);
+      i += 3;
+    } else {
+      res.push(bytes[i]);
+      i += 1;
+    }
+  }
+  Some(res)
+}
+
+fn normalize_path(path: &Path) -> PathBuf {
+  let mut res = PathBuf::new();
+  for component in path.components() {
+    match component {
+      Component::ParentDir => {
+        res.pop();
+      }
+      Component::CurDir => {}
+      component => res.push(component),
+    }
+  }
+  res
+}
+
+fn source_map_warning(message: String, source_map: &SourceMap, span: Span) -> Diagnostic {
+  Diagnostic {
+    message,
+    code_highlights: Some(vec![CodeHighlight {
+      message: None,
+      loc: SourceLocation::from(source_map, span),
+    }]),
+    hints: None,
+    show_environment: false,
+    severity: DiagnosticSeverity::Warning,
+    documentation_url: None,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::transform;
+
+  fn transform_with_map(code: &str, input_source_map: Option<&str>) -> sourcemap::SourceMap {
+    let result = transform(Config {
+      source_maps: true,
+      detect_source_map_url: true,
+      input_source_map: input_source_map.map(|map| map.into()),
+      ..Config::new("index.js", code)
+    })
+    .unwrap();
+    assert!(result.diagnostics.is_none());
+    sourcemap::SourceMap::from_slice(result.map.unwrap().as_bytes()).unwrap()
+  }
+
+  #[test]
+  fn percent_encoded_data_url() {
+    // {"version":3,"sources":["a.ts"],"names":[],"mappings":"AAIA"}
+    let map = transform_with_map(
+      "let x = 1;\n//# sourceMappingURL=data:application/json,%7B%22version%22%3A3%2C%22sources%22%3A%5B%22a.ts%22%5D%2C%22names%22%3A%5B%5D%2C%22mappings%22%3A%22AAIA%22%7D",
+      None,
+    );
+    let token = map.lookup_token(0, 0).unwrap();
+    assert_eq!(token.get_source(), Some("a.ts"));
+    assert_eq!(token.get_src_line(), 4);
+  }
+
+  #[test]
+  fn source_root() {
+    let map = transform_with_map(
+      "let x = 1;",
+      Some(r#"{"version":3,"sourceRoot":"src/","sources":["a.ts"],"names":[],"mappings":"AAAA"}"#),
+    );
+    assert_eq!(
+      map.lookup_token(0, 0).unwrap().get_source(),
+      Some("src/a.ts")
+    );
+  }
+
+  #[test]
+  fn compose_across_lines() {
+    // The input maps line 0 and column 4 of line 1. Line 2 has no mappings.
+    let mut builder = sourcemap::SourceMapBuilder::new(None);
+    builder.add(0, 0, 10, 0, Some("a.js"), None);
+    builder.add(1, 4, 11, 2, Some("a.js"), None);
+    let input = builder.into_sourcemap();
+
+    let mut builder = sourcemap::SourceMapBuilder::new(None);
+    builder.add(0, 0, 0, 0, Some("index.js"), None);
+    builder.add(1, 0, 1, 0, Some("index.js"), None);
+    builder.add(2, 0, 2, 0, Some("index.js"), None);
+    let map = builder.into_sourcemap();
+
+    let composed = compose_source_maps(&map, &input);
+    let lines: Vec<_> = composed
+      .tokens()
+      .map(|token| {
+        (
+          token.get_dst_line(),
+          token.get_src_line(),
+          token.get_src_col(),
+        )
+      })
+      .collect();
+    assert_eq!(lines, vec![(0, 10, 0), (1, 11, 2), (2, 11, 2)]);
+  }
+}
@@ -0,0 +1,120 @@
+use serde::{Deserialize, Serialize};
+use swc_common::comments::{Comment, CommentKind, Comments, SingleThreadedComments};
+use swc_common::{BytePos, SourceMap};
+
+use crate::utils::SourceLocation;
+
+/// Where legal comments (license and copyright notices) end up in the output.
+#[derive(Serialize, Debug, Deserialize, Clone, Copy, Eq, PartialEq)]
+pub enum LegalComments {
+  /// Keep them where they are in the source, even when the output is minified.
+  Inline,
+  /// Move them to the top of the output.
+  Top,
+  /// Remove them from the output, e.g. to write them to a separate LICENSE file.
+  Strip,
+}
+
+impl Default for LegalComments {
+  fn default() -> Self {
+    LegalComments::Inline
+  }
+}
+
+/// A legal comment found in the source. The text includes the comment delimiters.
+#[derive(Serialize, Debug, Deserialize)]
+pub struct LegalComment {
+  pub text: String,
+  pub loc: SourceLocation,
+}
+
+impl LegalComment {
+  pub fn from(source_map: &SourceMap, comment: &Comment) -> Self {
+    LegalComment {
+      text: comment_to_string(comment),
+      loc: SourceLocation::from(source_map, comment.span),
+    }
+  }
+}
+
+/// Comments starting with `!`, or containing `@license` or `@preserve`, are considered legal
+/// comments, following the convention of other minifiers.
+pub fn is_legal_comment(comment: &Comment) -> bool {
+  comment.text.starts_with('!')
+    || comment.text.contains("@license")
+    || comment.text.contains("@preserve")
+}
+
+/// Returns the legal comments of a module in source order. Unless they are kept inline,
+/// they are removed so that they are not emitted in place.
+pub fn extract_legal_comments(
+  comments: &SingleThreadedComments,
+  mode: LegalComments,
+) -> Vec<Comment> {
+  let mut legal_comments = if mode == LegalComments::Inline {
+    let (leading, trailing) = comments.borrow_all();
+    leading
+      .values()
+      .chain(trailing.values())
+      .flatten()
+      .filter(|comment| is_legal_comment(comment))
+      .cloned()
+      .collect()
+  } else {
+    retain_comments(comments, |comment| !is_legal_comment(comment))
+  };
+  legal_comments.sort_by_key(|comment| comment.span.lo);
+  legal_comments
+}
+
+/// Removes all comments that don't match the predicate, and returns them.
+pub fn retain_comments<F>(comments: &SingleThreadedComments, f: F) -> Vec<Comment>
+where
+  F: Fn(&Comment) -> bool,
+{
+  let (leading, trailing): (Vec<BytePos>, Vec<BytePos>) = {
+    let (leading, trailing) = comments.borrow_all();
+    (
+      leading.keys().copied().collect(),
+      trailing.keys().copied().collect(),
+    )
+  };
+
+  let mut removed = vec![];
+  for pos in leading {
+    if let Some(list) = comments.take_leading(pos) {
+      let (kept, rest): (Vec<Comment>, Vec<Comment>) = list.into_iter().partition(|c| f(c));
+      if !kept.is_empty() {
+        comments.add_leading_comments(pos, kept);
+      }
+      removed.extend(rest);
+    }
+  }
+  for pos in trailing {
+    if let Some(list) = comments.take_trailing(pos) {
+      let (kept, rest): (Vec<Comment>, Vec<Comment>) = list.into_iter().partition(|c| f(c));
+      if !kept.is_empty() {
+        comments.add_trailing_comments(pos, kept);
+      }
+      removed.extend(rest);
+    }
+  }
+  removed
+}
+
+/// Formats legal comments to be inserted at the top of the output, one per line.
+pub fn legal_comments_header(comments: &[Comment]) -> String {
+  let mut header = String::new();
+  for comment in comments {
+    header.push_str(&comment_to_string(comment));
+    header.push('\n');
+  }
+  header
+}
+
+fn comment_to_string(comment: &Comment) -> String {
+  match comment.kind {
+    CommentKind::Line => format!("//{}", comment.text),
+    CommentKind::Block => format!("/*{}*/", comment.text),
+  }
+}
@@ -3,6 +3,7 @@ extern crate swc_ecma_preset_env;
 extern crate swc_ecmascript;
 #[macro_use]
 extern crate swc_atoms;
+extern crate browserslist;
 extern crate data_encoding;
 extern crate dunce;
 extern crate inflector;
@@ -11,122 +12,161 @@ extern crate pathdiff;
 extern crate serde;
 extern crate serde_bytes;
 extern crate sha1;
+extern crate sourcemap;
 
+mod accessors;
 mod decl_collector;
+mod decorator_metadata;
 mod dependency_collector;
 mod env_replacer;
 mod fs;
 mod global_replacer;
 mod hoist;
+mod input_source_map;
+mod legal_comments;
 mod modules;
+mod passes;
+mod profile;
+mod standard_decorators;
+mod targets;
 mod utils;
 
 use std::collections::{HashMap, HashSet};
+use std::panic::AssertUnwindSafe;
 use std::path::{Path, PathBuf};
-use std::str::FromStr;
 
 use path_slash::PathExt;
 use serde::{Deserialize, Serialize};
 use swc_common::comments::SingleThreadedComments;
-use swc_common::errors::{DiagnosticBuilder, Emitter, Handler};
-use swc_common::{chain, sync::Lrc, FileName, Globals, Mark, SourceMap};
-use swc_ecma_preset_env::{preset_env, Mode::Entry, Targets, Version, Versions};
+use swc_common::errors::{DiagnosticBuilder, Emitter, Handler, Level};
+use swc_common::{chain, sync::Lrc, BytePos, FileName, Globals, Mark, SourceMap};
+use swc_ecma_preset_env::{preset_env, Feature, Mode::Entry};
 use swc_ecmascript::ast::Module;
 use swc_ecmascript::codegen::text_writer::JsWriter;
+use swc_ecmascript::minifier::optimize;
+use swc_ecmascript::minifier::option::{
+  CompressOptions, ExtraOptions, MangleOptions, MinifyOptions,
+};
+use swc_ecmascript::parser::error::Error as ParserError;
 use swc_ecmascript::parser::lexer::Lexer;
-use swc_ecmascript::parser::{EsConfig, PResult, Parser, StringInput, Syntax, TsConfig};
+use swc_ecmascript::parser::{EsConfig, Parser, StringInput, Syntax, TsConfig};
 use swc_ecmascript::transforms::resolver::resolver_with_mark;
 use swc_ecmascript::transforms::{
   compat::reserved_words::reserved_words, fixer, helpers, hygiene,
   optimization::simplify::dead_branch_remover, optimization::simplify::expr_simplifier,
   pass::Optional, proposals::decorators, react, typescript,
 };
+use swc_ecmascript::utils::HANDLER;
 use swc_ecmascript::visit::FoldWith;
 
+use accessors::strip_accessors;
 use decl_collector::*;
+use decorator_metadata::decorator_metadata;
 use dependency_collector::*;
+pub use dependency_collector::{DependencyDescriptor, DependencyKind};
 use env_replacer::*;
 use fs::inline_fs;
 use global_replacer::GlobalReplacer;
 use hoist::hoist;
+use input_source_map::{compose_source_maps, load_input_source_map};
+use legal_comments::{
+  extract_legal_comments, is_legal_comment, legal_comments_header, retain_comments,
+};
+pub use legal_comments::{LegalComment, LegalComments};
 use modules::esm2cjs;
-use utils::{CodeHighlight, Diagnostic, DiagnosticSeverity, SourceLocation, SourceType};
+pub use passes::{Pass, PassContext, PassRegistry, Stage};
+use profile::{PassProfile, Profiler};
+use standard_decorators::standard_decorators;
+use targets::targets_to_versions;
+pub use targets::Targets;
+use utils::{get_directives, has_module_decls};
+pub use utils::{
+  CodeHighlight, DecoratorsVersion, Diagnostic, DiagnosticSeverity, SourceLocation, SourceType,
+};
 
 type SourceMapBuffer = Vec<(swc_common::BytePos, swc_common::LineCol)>;
 
-#[derive(Serialize, Debug, Deserialize)]
+/// Options for a transform. Every option is required when deserializing, apart from
+/// optional ones. `Config::default()` disables every feature.
+#[derive(Serialize, Debug, Deserialize, Default)]
 pub struct Config {
-  filename: String,
+  pub filename: String,
   #[serde(with = "serde_bytes")]
-  code: Vec<u8>,
-  module_id: String,
-  project_root: String,
-  replace_env: bool,
-  env: HashMap<swc_atoms::JsWord, swc_atoms::JsWord>,
-  inline_fs: bool,
-  insert_node_globals: bool,
-  is_browser: bool,
-  is_worker: bool,
-  is_type_script: bool,
-  is_jsx: bool,
-  jsx_pragma: Option<String>,
-  jsx_pragma_frag: Option<String>,
-  automatic_jsx_runtime: bool,
-  jsx_import_source: Option<String>,
-  decorators: bool,
-  is_development: bool,
-  react_refresh: bool,
-  targets: Option<HashMap<String, String>>,
-  source_maps: bool,
-  scope_hoist: bool,
-  source_type: SourceType,
-  supports_module_workers: bool,
-  is_library: bool,
-  is_esm_output: bool,
-  trace_bailouts: bool,
+  pub code: Vec<u8>,
+  pub module_id: String,
+  pub project_root: String,
+  pub replace_env: bool,
+  pub env: HashMap<swc_atoms::JsWord, swc_atoms::JsWord>,
+  pub inline_fs: bool,
+  pub glob_dependencies: bool,
+  pub insert_node_globals: bool,
+  pub is_browser: bool,
+  pub is_worker: bool,
+  pub is_type_script: bool,
+  pub is_jsx: bool,
+  pub jsx_pragma: Option<String>,
+  pub jsx_pragma_frag: Option<String>,
+  pub automatic_jsx_runtime: bool,
+  pub jsx_import_source: Option<String>,
+  pub decorators: bool,
+  pub decorators_version: DecoratorsVersion,
+  pub emit_decorator_metadata: bool,
+  pub is_development: bool,
+  pub react_refresh: bool,
+  pub targets: Option<Targets>,
+  pub source_maps: bool,
+  pub minify: bool,
+  pub legal_comments: LegalComments,
+  pub input_source_map: Option<String>,
+  pub detect_source_map_url: bool,
+  pub scope_hoist: bool,
+  pub preserve_esm: bool,
+  pub source_type: SourceType,
+  pub supports_module_workers: bool,
+  pub is_library: bool,
+  pub is_esm_output: bool,
+  pub trace_bailouts: bool,
+  pub profile: bool,
 }
 
 #[derive(Serialize, Debug, Deserialize, Default)]
 pub struct TransformResult {
   #[serde(with = "serde_bytes")]
-  code: Vec<u8>,
-  map: Option<String>,
-  shebang: Option<String>,
-  dependencies: Vec<DependencyDescriptor>,
-  hoist_result: Option<hoist::HoistResult>,
-  diagnostics: Option<Vec<Diagnostic>>,
-  needs_esm_helpers: bool,
-  used_env: HashSet<swc_atoms::JsWord>,
+  pub code: Vec<u8>,
+  pub map: Option<String>,
+  pub shebang: Option<String>,
+  pub directives: Vec<swc_atoms::JsWord>,
+  pub legal_comments: Vec<LegalComment>,
+  pub dependencies: Vec<DependencyDescriptor>,
+  pub hoist_result: Option<hoist::HoistResult>,
+  pub diagnostics: Option<Vec<Diagnostic>>,
+  pub needs_esm_helpers: bool,
+  /// Whether the import and export statements were kept, see `Config::preserve_esm`.
+  pub preserved_esm: bool,
+  pub used_env: HashSet<swc_atoms::JsWord>,
+  pub profile: Option<Vec<PassProfile>>,
 }
 
-fn targets_to_versions(targets: &Option<HashMap<String, String>>) -> Option<Versions> {
-  if let Some(targets) = targets {
-    macro_rules! set_target {
-      ($versions: ident, $name: ident) => {
-        let version = targets.get(stringify!($name));
-        if let Some(version) = version {
-          if let Ok(version) = Version::from_str(version.as_str()) {
-            $versions.$name = Some(version);
-          }
-        }
-      };
+impl Config {
+  /// Creates a config for transforming `code`, with the default options.
+  /// Other options can be set with struct update syntax:
+  ///
+  /// ```
+  /// use parcel_js_swc_core::Config;
+  ///
+  /// let config = Config {
+  ///   is_type_script: true,
+  ///   scope_hoist: true,
+  ///   ..Config::new("src/index.ts", "export const x: number = 1;")
+  /// };
+  /// ```
+  pub fn new<F: Into<String>, C: Into<Vec<u8>>>(filename: F, code: C) -> Self {
+    Config {
+      filename: filename.into(),
+      code: code.into(),
+      ..Default::default()
     }
-
-    let mut versions = Versions::default();
-    set_target!(versions, chrome);
-    set_target!(versions, opera);
-    set_target!(versions, edge);
-    set_target!(versions, firefox);
-    set_target!(versions, safari);
-    set_target!(versions, ie);
-    set_target!(versions, ios);
-    set_target!(versions, android);
-    set_target!(versions, node);
-    set_target!(versions, electron);
-    return Some(versions);
   }
-
-  None
 }
 
 #[derive(Debug, Clone, Default)]
@@ -138,75 +178,154 @@ impl Emitter for ErrorBuffer {
   }
 }
 
+/// Converts the diagnostics emitted to an ErrorBuffer into Parcel diagnostics, draining the buffer.
+fn error_buffer_to_diagnostics(
+  error_buffer: &ErrorBuffer,
+  source_map: &SourceMap,
+) -> Vec<Diagnostic> {
+  let s = std::mem::take(&mut *error_buffer.0.lock().unwrap());
+  s.iter()
+    .map(|diagnostic| {
+      let message = diagnostic.message();
+      let span = diagnostic.span.clone();
+      let suggestions = diagnostic.suggestions.clone();
+
+      let span_labels = span.span_labels();
+      let code_highlights = if !span_labels.is_empty() {
+        let mut highlights = vec![];
+        for span_label in span_labels {
+          highlights.push(CodeHighlight {
+            message: span_label.label,
+            loc: SourceLocation::from(source_map, span_label.span),
+          });
+        }
+
+        Some(highlights)
+      } else {
+        None
+      };
+
+      let hints = if !suggestions.is_empty() {
+        Some(
+          suggestions
+            .into_iter()
+            .map(|suggestion| suggestion.msg)
+            .collect(),
+        )
+      } else {
+        None
+      };
+
+      let severity = match diagnostic.level {
+        Level::Warning | Level::Note | Level::Help => DiagnosticSeverity::Warning,
+        _ => DiagnosticSeverity::Error,
+      };
+
+      Diagnostic {
+        message,
+        code_highlights,
+        hints,
+        show_environment: false,
+        severity,
+        documentation_url: None,
+      }
+    })
+    .collect()
+}
+
 pub fn transform(config: Config) -> Result<TransformResult, std::io::Error> {
+  transform_with_passes(config, &PassRegistry::default())
+}
+
+/// Transforms a module like `transform`, running the given custom passes at their stages.
+pub fn transform_with_passes(
+  config: Config,
+  passes: &PassRegistry,
+) -> Result<TransformResult, std::io::Error> {
+  let source_map = Lrc::new(SourceMap::default());
+  let error_buffer = ErrorBuffer::default();
+  let handler = Handler::with_emitter(true, false, Box::new(error_buffer.clone()));
+
+  // Run the pipeline with an error handler installed and catch any panics, so that a
+  // pathological file produces diagnostics instead of taking down the whole process.
+  let res = std::panic::catch_unwind(AssertUnwindSafe(|| {
+    HANDLER.set(&handler, || {
+      transform_module(&config, passes, &source_map, &handler, &error_buffer)
+    })
+  }));
+
+  match res {
+    Ok(res) => res,
+    Err(payload) => {
+      let reason = if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+      } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+      } else {
+        String::from("unknown panic")
+      };
+
+      let mut diagnostics = error_buffer_to_diagnostics(&error_buffer, &source_map);
+      diagnostics.push(Diagnostic {
+        message: format!(
+          "Unexpected error while transforming {}: {}",
+          config.filename, reason
+        ),
+        code_highlights: None,
+        hints: None,
+        show_environment: false,
+        severity: DiagnosticSeverity::Error,
+        documentation_url: None,
+      });
+
+      Ok(TransformResult {
+        diagnostics: Some(diagnostics),
+        ..Default::default()
+      })
+    }
+  }
+}
+
+fn transform_module(
+  config: &Config,
+  passes: &PassRegistry,
+  source_map: &Lrc<SourceMap>,
+  handler: &Handler,
+  error_buffer: &ErrorBuffer,
+) -> Result<TransformResult, std::io::Error> {
   let mut result = TransformResult::default();
   let mut map_buf = vec![];
+  let mut profiler = Profiler::new(config.profile);
 
   let code = unsafe { std::str::from_utf8_unchecked(&config.code) };
-  let source_map = Lrc::new(SourceMap::default());
   let module = parse(
     code,
     config.project_root.as_str(),
     config.filename.as_str(),
-    &source_map,
-    &config,
+    source_map,
+    config,
   );
 
   match module {
-    Err(err) => {
-      let error_buffer = ErrorBuffer::default();
-      let handler = Handler::with_emitter(true, false, Box::new(error_buffer.clone()));
-      err.into_diagnostic(&handler).emit();
-
-      let s = error_buffer.0.lock().unwrap().clone();
-      let diagnostics: Vec<Diagnostic> = s
-        .iter()
-        .map(|diagnostic| {
-          let message = diagnostic.message();
-          let span = diagnostic.span.clone();
-          let suggestions = diagnostic.suggestions.clone();
-
-          let span_labels = span.span_labels();
-          let code_highlights = if !span_labels.is_empty() {
-            let mut highlights = vec![];
-            for span_label in span_labels {
-              highlights.push(CodeHighlight {
-                message: span_label.label,
-                loc: SourceLocation::from(&source_map, span_label.span),
-              });
-            }
-
-            Some(highlights)
-          } else {
-            None
-          };
-
-          let hints = if !suggestions.is_empty() {
-            Some(
-              suggestions
-                .into_iter()
-                .map(|suggestion| suggestion.msg)
-                .collect(),
-            )
-          } else {
-            None
-          };
-
-          Diagnostic {
-            message,
-            code_highlights,
-            hints,
-            show_environment: false,
-            severity: DiagnosticSeverity::Error,
-            documentation_url: None,
-          }
-        })
-        .collect();
-
-      result.diagnostics = Some(diagnostics);
+    Err(errors) => {
+      for err in errors {
+        let mut diagnostic = err.into_diagnostic(handler);
+        suggest_import_assertions(&mut diagnostic, source_map);
+        diagnostic.emit();
+      }
+      result.diagnostics = Some(error_buffer_to_diagnostics(error_buffer, source_map));
       Ok(result)
     }
-    Ok((module, comments)) => {
+    Ok((module, comments, errors, accessors)) => {
+      profiler.record(&mut result.profile, "parse", &module);
+
+      // The module could still be produced, so recoverable errors are reported as warnings.
+      for err in errors {
+        let mut diagnostic = err.into_diagnostic(handler);
+        diagnostic.level = Level::Warning;
+        diagnostic.emit();
+      }
+
       let mut module = module;
       result.shebang = match module.shebang {
         Some(shebang) => {
@@ -216,8 +335,25 @@ pub fn transform(config: Config) -> Result<TransformResult, std::io::Error> {
         None => None,
       };
 
+      result.directives = get_directives(&module);
+
+      let legal_comments = extract_legal_comments(&comments, config.legal_comments);
+      result.legal_comments = legal_comments
+        .iter()
+        .map(|comment| LegalComment::from(source_map, comment))
+        .collect();
+
       let mut global_deps = vec![];
       let mut fs_deps = vec![];
+      let mut diagnostics = vec![];
+      let input_source_map =
+        match load_input_source_map(config, source_map, &comments, &mut fs_deps) {
+          Ok(map) => map,
+          Err(diagnostic) => {
+            diagnostics.push(diagnostic);
+            None
+          }
+        };
       let should_inline_fs = config.inline_fs
         && config.source_type != SourceType::Script
         && code.contains("readFileSync");
@@ -225,6 +361,44 @@ pub fn transform(config: Config) -> Result<TransformResult, std::io::Error> {
         helpers::HELPERS.set(
           &helpers::Helpers::new(/* external helpers from @swc/helpers */ true),
           || {
+            let global_mark = Mark::fresh(Mark::root());
+            let ignore_mark = Mark::fresh(Mark::root());
+
+            macro_rules! run_passes {
+              ($stage: expr, $module: expr) => {
+                passes.run(
+                  $stage,
+                  $module,
+                  &mut PassContext {
+                    filename: &config.filename,
+                    source_map,
+                    global_mark,
+                    diagnostics: &mut diagnostics,
+                  },
+                )
+              };
+            }
+
+            module = run_passes!(Stage::AfterParse, module);
+
+            let versions = config
+              .targets
+              .as_ref()
+              .map(|targets| targets_to_versions(targets, &mut diagnostics));
+
+            if config.decorators && config.decorators_version == DecoratorsVersion::Standard {
+              let lower_class_fields = match versions {
+                Some(versions) => Feature::ClassProperties.should_enable(versions, false, false),
+                None => false,
+              };
+              module = module.fold_with(&mut standard_decorators(
+                source_map,
+                &mut diagnostics,
+                lower_class_fields,
+                &accessors,
+              ));
+            }
+
             let mut react_options = react::Options::default();
             if config.is_jsx {
               react_options.use_spread = true;
@@ -252,44 +426,48 @@ pub fn transform(config: Config) -> Result<TransformResult, std::io::Error> {
             }
 
             module = {
+              let legacy_decorators =
+                config.decorators && config.decorators_version == DecoratorsVersion::Legacy;
               let mut passes = chain!(
                 Optional::new(
                   react::react(source_map.clone(), Some(&comments), react_options),
                   config.is_jsx
                 ),
                 // Decorators can use type information, so must run before the TypeScript pass.
+                Optional::new(
+                  decorator_metadata(),
+                  legacy_decorators && config.emit_decorator_metadata && config.is_type_script
+                ),
                 Optional::new(
                   decorators::decorators(decorators::Config {
                     legacy: true,
-                    // Always disabled for now, SWC's implementation doesn't match TSC.
+                    // SWC's implementation doesn't match TSC, metadata is added by the pass above instead.
                     emit_metadata: false
                   }),
-                  config.decorators
+                  legacy_decorators
                 ),
                 Optional::new(typescript::strip(), config.is_type_script)
               );
 
               module.fold_with(&mut passes)
             };
+            profiler.record(&mut result.profile, "react_decorators_typescript", &module);
 
-            let global_mark = Mark::fresh(Mark::root());
-            let ignore_mark = Mark::fresh(Mark::root());
             let module = module.fold_with(&mut resolver_with_mark(global_mark));
+            let module = run_passes!(Stage::AfterTypeScript, module);
             let mut decls = collect_decls(&module);
 
             let mut preset_env_config = swc_ecma_preset_env::Config {
               dynamic_import: true,
               ..Default::default()
             };
-            let versions = targets_to_versions(&config.targets);
             if let Some(versions) = versions {
-              preset_env_config.targets = Some(Targets::Versions(versions));
+              preset_env_config.targets = Some(swc_ecma_preset_env::Targets::Versions(versions));
               preset_env_config.shipped_proposals = true;
               preset_env_config.mode = Some(Entry);
               preset_env_config.bugfixes = true;
             }
 
-            let mut diagnostics = vec![];
             let module = {
               let mut passes = chain!(
                 // Inline process.env and process.browser
@@ -300,7 +478,7 @@ pub fn transform(config: Config) -> Result<TransformResult, std::io::Error> {
                     is_browser: config.is_browser,
                     decls: &decls,
                     used_env: &mut result.used_env,
-                    source_map: &source_map,
+                    source_map,
                     diagnostics: &mut diagnostics
                   },
                   config.source_type != SourceType::Script
@@ -325,13 +503,14 @@ pub fn transform(config: Config) -> Result<TransformResult, std::io::Error> {
 
               module.fold_with(&mut passes)
             };
+            profiler.record(&mut result.profile, "env_fs_inlining", &module);
 
             let module = {
               let mut passes = chain!(
                 // Insert dependencies for node globals
                 Optional::new(
                   GlobalReplacer {
-                    source_map: &source_map,
+                    source_map,
                     items: &mut global_deps,
                     globals: HashMap::new(),
                     project_root: Path::new(&config.project_root),
@@ -353,19 +532,24 @@ pub fn transform(config: Config) -> Result<TransformResult, std::io::Error> {
 
               module.fold_with(&mut passes)
             };
+            profiler.record(&mut result.profile, "globals_preset_env", &module);
 
+            let module = run_passes!(Stage::BeforeDependencies, module);
             let module = module.fold_with(
               // Collect dependencies
               &mut dependency_collector(
-                &source_map,
+                source_map,
                 &mut result.dependencies,
                 &decls,
                 ignore_mark,
-                &config,
+                config,
+                &comments,
                 &mut diagnostics,
               ),
             );
+            profiler.record(&mut result.profile, "dependency_collector", &module);
 
+            diagnostics.extend(error_buffer_to_diagnostics(error_buffer, source_map));
             if diagnostics
               .iter()
               .any(|d| d.severity == DiagnosticSeverity::Error)
@@ -388,40 +572,117 @@ pub fn transform(config: Config) -> Result<TransformResult, std::io::Error> {
                 Ok((module, hoist_result, hoist_diagnostics)) => {
                   result.hoist_result = Some(hoist_result);
                   diagnostics.extend(hoist_diagnostics);
+                  profiler.record(&mut result.profile, "hoist", &module);
                   module
                 }
-                Err(diagnostics) => {
+                Err(hoist_diagnostics) => {
+                  diagnostics.extend(hoist_diagnostics);
                   result.diagnostics = Some(diagnostics);
                   return Ok(result);
                 }
               }
+            } else if config.preserve_esm && has_module_decls(&module) {
+              // Keep import and export statements, which were rewritten to dependency
+              // placeholders, so the output can be loaded as a native ES module.
+              result.preserved_esm = true;
+              profiler.record(&mut result.profile, "preserve_esm", &module);
+              module
             } else {
               let (module, needs_helpers) = esm2cjs(module, versions);
               result.needs_esm_helpers = needs_helpers;
+              profiler.record(&mut result.profile, "esm2cjs", &module);
               module
             };
+            let module = run_passes!(Stage::AfterHoist, module);
 
             let program = {
               let mut passes = chain!(reserved_words(), hygiene(), fixer(Some(&comments)),);
               module.fold_with(&mut passes)
             };
+            profiler.record(&mut result.profile, "hygiene_fixer", &program);
+
+            // Compress and mangle the output. Top level bindings are neither renamed, inlined nor
+            // dropped, so the packager can still find the hoisted `$id$import$…` and `$id$export$…`
+            // names and the `$parcel$…` helpers when the bundle is joined.
+            let minify = config.minify;
+            let program = if minify {
+              let program = optimize(
+                program,
+                source_map.clone(),
+                Some(&comments),
+                None,
+                &MinifyOptions {
+                  compress: Some(CompressOptions {
+                    top_level: None,
+                    top_retain: vec![],
+                    ..Default::default()
+                  }),
+                  mangle: Some(MangleOptions {
+                    top_level: false,
+                    ..Default::default()
+                  }),
+                  ..Default::default()
+                },
+                &ExtraOptions {
+                  top_level_mark: global_mark,
+                },
+              );
+              let program = program.fold_with(&mut fixer(Some(&comments)));
+              profiler.record(&mut result.profile, "minify", &program);
+              program
+            } else {
+              program
+            };
 
             result.dependencies.extend(global_deps);
             result.dependencies.extend(fs_deps);
 
+            diagnostics.extend(error_buffer_to_diagnostics(error_buffer, source_map));
+            if diagnostics
+              .iter()
+              .any(|d| d.severity == DiagnosticSeverity::Error)
+            {
+              result.diagnostics = Some(diagnostics);
+              return Ok(result);
+            }
+
             if !diagnostics.is_empty() {
               result.diagnostics = Some(diagnostics);
             }
 
-            let (buf, mut src_map_buf) =
-              emit(source_map.clone(), comments, &program, config.source_maps)?;
-            if config.source_maps
-              && source_map
-                .build_source_map(&mut src_map_buf)
-                .to_writer(&mut map_buf)
-                .is_ok()
-            {
-              result.map = Some(String::from_utf8(map_buf).unwrap());
+            // Minified output only keeps the legal comments that are kept inline.
+            let emit_comments = if !minify {
+              Some(&comments)
+            } else if config.legal_comments == LegalComments::Inline {
+              retain_comments(&comments, is_legal_comment);
+              Some(&comments)
+            } else {
+              None
+            };
+            let (mut buf, mut src_map_buf) = emit(
+              source_map.clone(),
+              emit_comments,
+              &program,
+              config.source_maps,
+              minify,
+            )?;
+            if config.legal_comments == LegalComments::Top && !legal_comments.is_empty() {
+              let header = legal_comments_header(&legal_comments);
+              let lines = header.matches('\n').count() as u32;
+              for (_, line_col) in src_map_buf.iter_mut() {
+                line_col.line += lines;
+              }
+              buf.splice(0..0, header.into_bytes());
+            }
+            profiler.record(&mut result.profile, "codegen", &program);
+            if config.source_maps {
+              let mut map = source_map.build_source_map(&mut src_map_buf);
+              if let Some(input_source_map) = &input_source_map {
+                map = compose_source_maps(&map, input_source_map);
+              }
+              if map.to_writer(&mut map_buf).is_ok() {
+                result.map = Some(String::from_utf8(map_buf).unwrap());
+              }
             }
             result.code = buf;
             Ok(result)
@@ -432,13 +693,42 @@ pub fn transform(config: Config) -> Result<TransformResult, std::io::Error> {
   }
 }
 
+/// The parser only supports import attributes with the `assert` keyword, so suggest it
+/// when the newer `with` keyword follows a module specifier.
+fn suggest_import_assertions(diagnostic: &mut DiagnosticBuilder, source_map: &SourceMap) {
+  let span = match diagnostic.span.primary_span() {
+    Some(span) => span,
+    None => return,
+  };
+  let is_with = matches!(source_map.span_to_snippet(span), Ok(snippet) if snippet == "with");
+  let follows_specifier = matches!(
+    source_map.span_to_prev_source(span),
+    Ok(prev) if prev.trim_end().ends_with(['"', '\''])
+  );
+  if is_with && follows_specifier {
+    diagnostic.span_suggestion(
+      span,
+      "Import attributes are only supported with `assert`, e.g. `assert { type: 'json' }`.",
+      "assert".into(),
+    );
+  }
+}
+
+/// The module, its comments, recoverable errors, and the positions of `accessor` fields.
+type ParseResult = (
+  Module,
+  SingleThreadedComments,
+  Vec<ParserError>,
+  Vec<BytePos>,
+);
+
 fn parse(
   code: &str,
   project_root: &str,
   filename: &str,
   source_map: &Lrc<SourceMap>,
   config: &Config,
-) -> PResult<(Module, SingleThreadedComments)> {
+) -> Result<ParseResult, Vec<ParserError>> {
   // Attempt to convert the path to be relative to the project root.
   // If outside the project root, use an absolute path so that if the project root moves the path still works.
   let filename: PathBuf = if let Ok(relative) = Path::new(filename).strip_prefix(project_root) {
@@ -454,6 +744,7 @@ fn parse(
       tsx: config.is_jsx,
       dynamic_import: true,
       decorators: config.decorators,
+      import_assertions: true,
       ..Default::default()
     })
   } else {
@@ -464,29 +755,49 @@ fn parse(
       export_namespace_from: true,
       import_meta: true,
       decorators: config.decorators,
+      // Standard decorators can come before or after `export`, but only one is supported by the parser.
+      decorators_before_export: config.decorators_version == DecoratorsVersion::Standard,
+      import_assertions: true,
       ..Default::default()
     })
   };
 
-  let lexer = Lexer::new(
-    syntax,
-    Default::default(),
-    StringInput::from(&*source_file),
-    Some(&comments),
-  );
+  // Auto-accessors are only lowered along with standard decorators. The stripped code keeps the
+  // same positions, so the source map still refers to the original code.
+  let stripped = if config.decorators && config.decorators_version == DecoratorsVersion::Standard {
+    strip_accessors(&source_file, syntax)
+  } else {
+    None
+  };
+  let (input, accessors) = match &stripped {
+    Some((code, accessors)) => (
+      StringInput::new(code, source_file.start_pos, source_file.end_pos),
+      accessors.clone(),
+    ),
+    None => (StringInput::from(&*source_file), vec![]),
+  };
+
+  let lexer = Lexer::new(syntax, Default::default(), input, Some(&comments));
 
   let mut parser = Parser::new_from(lexer);
-  match parser.parse_module() {
-    Err(err) => Err(err),
-    Ok(module) => Ok((module, comments)),
+  let module = parser.parse_module();
+  // The parser collects recoverable errors separately from the result.
+  let mut errors = parser.take_errors();
+  match module {
+    Err(err) => {
+      errors.push(err);
+      Err(errors)
+    }
+    Ok(module) => Ok((module, comments, errors, accessors)),
   }
 }
 
 fn emit(
   source_map: Lrc<SourceMap>,
-  comments: SingleThreadedComments,
+  comments: Option<&SingleThreadedComments>,
   program: &Module,
   source_maps: bool,
+  minify: bool,
 ) -> Result<(Vec<u8>, SourceMapBuffer), std::io::Error> {
   let mut src_map_buf = vec![];
   let mut buf = vec![];
@@ -501,10 +812,10 @@ fn emit(
         None
       },
     ));
-    let config = swc_ecmascript::codegen::Config { minify: false };
+    let config = swc_ecmascript::codegen::Config { minify };
     let mut emitter = swc_ecmascript::codegen::Emitter {
       cfg: config,
-      comments: Some(&comments),
+      comments: comments.map(|comments| comments as &dyn swc_common::comments::Comments),
       cm: source_map,
       wr: writer,
     };
@@ -514,3 +825,986 @@ fn emit(
 
   Ok((buf, src_map_buf))
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use swc_common::{BytePos, Span};
+
+  fn transform_code(code: &str, config: Config) -> TransformResult {
+    transform(Config {
+      code: code.into(),
+      filename: "index.js".into(),
+      ..config
+    })
+    .unwrap()
+  }
+
+  #[test]
+  fn panic_to_diagnostic() {
+    let mut passes = PassRegistry::new();
+    passes.register(
+      Stage::AfterTypeScript,
+      |_module: Module, _ctx: &mut PassContext| panic!("boom"),
+    );
+
+    let result = transform_with_passes(Config::new("index.js", "let x = 1;"), &passes).unwrap();
+    assert!(result.code.is_empty());
+    let diagnostics = result.diagnostics.unwrap();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+      diagnostics[0].message,
+      "Unexpected error while transforming index.js: boom"
+    );
+    assert_eq!(diagnostics[0].severity, DiagnosticSeverity::Error);
+  }
+
+  #[test]
+  fn error_buffer_diagnostics() {
+    let source_map = Lrc::new(SourceMap::default());
+    source_map.new_source_file(FileName::Anon, "let x = 1;\nlet y = 2;".into());
+    let error_buffer = ErrorBuffer::default();
+    let handler = Handler::with_emitter(true, false, Box::new(error_buffer.clone()));
+
+    let span = Span::new(BytePos(15), BytePos(16), Default::default());
+    handler
+      .struct_span_warn(span, "Unused variable")
+      .span_label(span, "declared here")
+      .span_suggestion(span, "Remove it", String::new())
+      .emit();
+    handler.struct_err("Something went wrong").emit();
+
+    let diagnostics = error_buffer_to_diagnostics(&error_buffer, &source_map);
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(diagnostics[0].message, "Unused variable");
+    assert_eq!(diagnostics[0].severity, DiagnosticSeverity::Warning);
+    assert_eq!(diagnostics[0].hints, Some(vec!["Remove it".into()]));
+    let highlights = diagnostics[0].code_highlights.as_ref().unwrap();
+    assert_eq!(highlights.len(), 1);
+    assert_eq!(highlights[0].message, Some("declared here".into()));
+    assert_eq!(
+      (highlights[0].loc.start_line, highlights[0].loc.start_col),
+      (2, 5)
+    );
+    assert_eq!(diagnostics[1].message, "Something went wrong");
+    assert_eq!(diagnostics[1].severity, DiagnosticSeverity::Error);
+    assert!(diagnostics[1].code_highlights.is_none());
+
+    // The buffer is drained.
+    assert!(error_buffer_to_diagnostics(&error_buffer, &source_map).is_empty());
+  }
+
+  #[test]
+  fn hoist_errors_keep_warnings() {
+    let result = transform_code(
+      "import {x} from 'y';\ndelete z;\nx = 2;",
+      Config {
+        scope_hoist: true,
+        ..Default::default()
+      },
+    );
+    let diagnostics = result.diagnostics.unwrap();
+    let messages: Vec<_> = diagnostics
+      .iter()
+      .map(|d| (&d.severity, d.message.as_str()))
+      .collect();
+    assert_eq!(
+      messages,
+      vec![
+        (
+          &DiagnosticSeverity::Warning,
+          "'delete' cannot be called on an identifier in strict mode"
+        ),
+        (
+          &DiagnosticSeverity::Error,
+          "Assignment to an import specifier is not allowed"
+        ),
+      ]
+    );
+  }
+
+  #[test]
+  fn minify() {
+    let code =
+      "function add(first, second) {\n  return first + second;\n}\nconsole.log(add(1, 2));";
+    let result = transform_code(
+      code,
+      Config {
+        minify: true,
+        ..Default::default()
+      },
+    );
+    let output = String::from_utf8(result.code).unwrap();
+    assert!(!output.contains('\n'));
+    assert!(!output.contains("first"));
+
+    // Scope hoisted names are left for the packager, even where they could be inlined or dropped.
+    let result = transform_code(
+      "import {a, b as c} from './a';
+      export const sum = a + 1;
+      const unused = c * 2;
+      export function add(first, second) {
+        return first + second + sum;
+      }
+      console.log(typeof global);",
+      Config {
+        module_id: "abc".into(),
+        minify: true,
+        scope_hoist: true,
+        insert_node_globals: true,
+        ..Default::default()
+      },
+    );
+    let output = String::from_utf8(result.code).unwrap();
+    assert!(!output.contains('\n'));
+    assert!(!output.contains("first"));
+    assert!(output.contains("import\"abc:./a\";"));
+    assert!(output.contains("$abc$import$96b34de064849cc8$407448d2b89b1813+1"));
+    assert!(output.contains("$abc$var$unused=2*$abc$import$96b34de064849cc8$8b22cf2602fb60ce"));
+    assert!(output.contains("return a+b+$abc$export$8a63f25cc62965f1"));
+    assert!(output.contains("typeof $parcel$global"));
+  }
+
+  #[test]
+  fn recoverable_errors_are_warnings() {
+    let result = transform_code("delete x;\nlet y = 1;", Config::default());
+    let diagnostics = result.diagnostics.unwrap();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+      diagnostics[0].message,
+      "'delete' cannot be called on an identifier in strict mode"
+    );
+    assert_eq!(diagnostics[0].severity, DiagnosticSeverity::Warning);
+    let output = String::from_utf8(result.code).unwrap();
+    assert!(output.contains("delete x;"));
+    assert!(output.contains("let y = 1;"));
+  }
+
+  #[test]
+  fn profile() {
+    let stages = |config: Config| -> Vec<String> {
+      transform_code(
+        "import x from 'x';\nexport default x;",
+        Config {
+          profile: true,
+          ..config
+        },
+      )
+      .profile
+      .unwrap()
+      .into_iter()
+      .map(|profile| profile.name)
+      .collect()
+    };
+
+    let cjs = stages(Config::default());
+    assert_eq!(
+      cjs,
+      [
+        "parse",
+        "react_decorators_typescript",
+        "env_fs_inlining",
+        "globals_preset_env",
+        "dependency_collector",
+        "esm2cjs",
+        "hygiene_fixer",
+        "codegen"
+      ]
+    );
+    let esm = stages(Config {
+      preserve_esm: true,
+      ..Default::default()
+    });
+    assert_eq!(esm[5..], ["preserve_esm", "hygiene_fixer", "codegen"]);
+    let hoisted = stages(Config {
+      scope_hoist: true,
+      ..Default::default()
+    });
+    assert_eq!(hoisted[5..], ["hoist", "hygiene_fixer", "codegen"]);
+
+    assert!(transform_code("let x;", Config::default())
+      .profile
+      .is_none());
+  }
+
+  #[test]
+  fn directives() {
+    let result = transform_code(
+      "#!/usr/bin/env node\n'use strict';\n\"use client\";\nfoo();\n'use server';",
+      Config::default(),
+    );
+    let directives: Vec<&str> = result.directives.iter().map(|d| &**d).collect();
+    assert_eq!(directives, vec!["use strict", "use client"]);
+
+    // A parenthesized string is an expression, not a directive.
+    let result = transform_code("'a';\n('use client');\n'b';", Config::default());
+    let directives: Vec<&str> = result.directives.iter().map(|d| &**d).collect();
+    assert_eq!(directives, vec!["a"]);
+  }
+
+  #[test]
+  fn boundary_dependencies() {
+    let dir = std::env::temp_dir().join("parcel-js-swc-boundary");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("data.txt"), "data").unwrap();
+
+    let code = "'use client';\nimport a from './a';\nrequire('./b');\nimport('./c');\nnew URL('d.png', import.meta.url);\nexport * from './e';\nprocess.cwd();\nconst fs = require('fs');\nfs.readFileSync(__dirname + '/data.txt', 'utf8');";
+    let transform_boundary = |code: &str, is_browser| {
+      let result = transform(Config {
+        code: code.into(),
+        filename: dir.join("index.js").to_str().unwrap().into(),
+        project_root: dir.to_str().unwrap().into(),
+        is_browser,
+        insert_node_globals: true,
+        inline_fs: true,
+        ..Default::default()
+      })
+      .unwrap();
+      assert!(result.diagnostics.is_none());
+      result
+        .dependencies
+        .into_iter()
+        .map(|dep| (dep.kind, dep.specifier.to_string(), dep.boundary))
+        .collect::<Vec<_>>()
+    };
+
+    // A client module compiled for the server is the entry of the client graph, so the
+    // dependencies in its code are marked. Inlined files and node globals are not, since they
+    // are used by the module itself in the environment it is compiled for.
+    let deps = transform_boundary(code, false);
+    let kinds: HashSet<String> = deps.iter().map(|(kind, ..)| kind.to_string()).collect();
+    for kind in &[
+      "Import",
+      "Export",
+      "Require",
+      "DynamicImport",
+      "Url",
+      "File",
+    ] {
+      assert!(kinds.contains(*kind), "missing {} dependency", kind);
+    }
+    let crossing = |deps: &[(DependencyKind, String, Option<Boundary>)]| {
+      deps
+        .iter()
+        .filter(|(kind, specifier, _)| *kind != DependencyKind::File && specifier != "process")
+        .map(|(.., boundary)| *boundary)
+        .collect::<Vec<_>>()
+    };
+    assert_eq!(crossing(&deps), vec![Some(Boundary::Client); 6]);
+    assert_eq!(
+      deps
+        .iter()
+        .filter(|(.., boundary)| boundary.is_none())
+        .count(),
+      deps.len() - 6
+    );
+
+    // It does not cross a boundary when compiled for the browser.
+    let deps = transform_boundary(code, true);
+    assert!(deps.iter().all(|(.., boundary)| boundary.is_none()));
+
+    let code = &code.replacen("use client", "use server", 1);
+    let deps = transform_boundary(code, true);
+    assert_eq!(crossing(&deps), vec![Some(Boundary::Server); 6]);
+    let deps = transform_boundary(code, false);
+    assert!(deps.iter().all(|(.., boundary)| boundary.is_none()));
+
+    // The JSX runtime is imported before the directive, but is a helper for the server.
+    let result = transform_code(
+      "'use client';\nimport a from './a';\nexport default () => <div />;",
+      Config {
+        is_jsx: true,
+        automatic_jsx_runtime: true,
+        ..Default::default()
+      },
+    );
+    let deps: Vec<_> = result
+      .dependencies
+      .iter()
+      .map(|dep| (dep.specifier.to_string(), dep.boundary))
+      .collect();
+    assert!(deps.contains(&("./a".into(), Some(Boundary::Client))));
+    assert!(deps.contains(&("react/jsx-runtime".into(), None)));
+  }
+
+  #[test]
+  fn import_attributes() {
+    use swc_ecmascript::ast::{KeyValueProp, ModuleDecl, ModuleItem, Prop, PropName, PropOrSpread};
+
+    let code = "import a from './a.json' assert { type: 'json' };\nexport * from './b.css' assert { type: 'css' };\nexport { c } from './c.json' assert { type: 'json' };\nimport('./d.json', { with: { type: 'json', other: 1 }, preload: true });\nimport('./e.json', { assert: { type: 'json' }, prefetch: true });\nconsole.log(a);";
+    let attributes = |result: &TransformResult| -> Vec<(String, Vec<(String, String)>)> {
+      result
+        .dependencies
+        .iter()
+        .map(|dep| {
+          let mut attributes: Vec<(String, String)> = dep
+            .attributes
+            .iter()
+            .flatten()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+          attributes.sort();
+          (dep.specifier.to_string(), attributes)
+        })
+        .collect()
+    };
+    let json = || vec![("type".to_string(), "json".to_string())];
+
+    let result = transform_code(code, Config::default());
+    assert_eq!(
+      attributes(&result),
+      vec![
+        ("./a.json".into(), json()),
+        ("./b.css".into(), vec![("type".into(), "css".into())]),
+        ("./c.json".into(), json()),
+        // Non-string values are not attributes.
+        ("./d.json".into(), json()),
+        ("./e.json".into(), json()),
+      ]
+    );
+    // Boolean options are resource hints instead.
+    let hints: Vec<_> = result
+      .dependencies
+      .iter()
+      .map(|dep| dep.resource_hint)
+      .collect();
+    assert_eq!(
+      hints,
+      vec![
+        None,
+        None,
+        None,
+        Some(ResourceHint::Preload),
+        Some(ResourceHint::Prefetch)
+      ]
+    );
+
+    // The hoisted imports keep their attributes.
+    let hoisted = std::sync::Arc::new(std::sync::Mutex::new(vec![]));
+    let mut passes = PassRegistry::new();
+    let found = hoisted.clone();
+    passes.register(
+      Stage::AfterHoist,
+      move |module: Module, _ctx: &mut PassContext| {
+        for item in &module.body {
+          if let ModuleItem::ModuleDecl(ModuleDecl::Import(import)) = item {
+            let keys: Vec<String> = import
+              .asserts
+              .iter()
+              .flat_map(|asserts| asserts.props.iter())
+              .filter_map(|prop| match prop {
+                PropOrSpread::Prop(prop) => match &**prop {
+                  Prop::KeyValue(KeyValueProp {
+                    key: PropName::Ident(key),
+                    ..
+                  }) => Some(key.sym.to_string()),
+                  _ => None,
+                },
+                _ => None,
+              })
+              .collect();
+            found
+              .lock()
+              .unwrap()
+              .push((import.src.value.to_string(), keys));
+          }
+        }
+        module
+      },
+    );
+    let result = transform_with_passes(
+      Config {
+        scope_hoist: true,
+        ..Config::new("index.js", code)
+      },
+      &passes,
+    )
+    .unwrap();
+    assert!(result.diagnostics.is_none());
+    let hoisted = hoisted.lock().unwrap();
+    for specifier in &["./a.json", "./b.css", "./c.json"] {
+      assert!(
+        hoisted
+          .iter()
+          .any(|(src, keys)| src.ends_with(specifier) && keys == &["type"]),
+        "{} was hoisted without its attributes",
+        specifier
+      );
+    }
+
+    // The `with` keyword is not supported by the parser.
+    let result = transform_code(
+      "import a from './a.json' with { type: 'json' };",
+      Config::default(),
+    );
+    let diagnostics = result.diagnostics.unwrap();
+    assert_eq!(
+      diagnostics[0].hints,
+      Some(vec![
+        "Import attributes are only supported with `assert`, e.g. `assert { type: 'json' }`."
+          .into()
+      ])
+    );
+  }
+
+  #[test]
+  fn glob_dependencies() {
+    let code = "const modules = import.meta.glob('./dir/*.js', { eager: true, import: 'default' });\nconst lazy = import.meta.glob('./dir/*.js');\nconst ctx = require.context('./dir/', false, /\\.js$/i, 'lazy');\nconst all = require.context('./dir');\nconst locale = import(`./locales/${lang}.json`);\nconst icon = require('./icons/' + name);\nconst flag = new URL(`./flags/${code}.svg`, import.meta.url);\nconst asset = new URL('./flags/fr.svg', import.meta.url);";
+    let result = transform_code(
+      code,
+      Config {
+        glob_dependencies: true,
+        ..Default::default()
+      },
+    );
+    assert!(result.diagnostics.is_none());
+    let globs: Vec<(&str, Option<&GlobOptions>)> = result
+      .dependencies
+      .iter()
+      .filter(|dep| dep.kind == DependencyKind::Glob)
+      .map(|dep| (&*dep.specifier, dep.glob.as_ref()))
+      .collect();
+    assert_eq!(
+      globs,
+      vec![
+        (
+          "./dir/*.js",
+          Some(&GlobOptions::ImportMetaGlob {
+            eager: true,
+            import: Some("default".into())
+          })
+        ),
+        (
+          "./dir/*.js",
+          Some(&GlobOptions::ImportMetaGlob {
+            eager: false,
+            import: None
+          })
+        ),
+        (
+          "./dir/*",
+          Some(&GlobOptions::RequireContext {
+            directory: "./dir/".into(),
+            recursive: false,
+            regexp: "\\.js$".into(),
+            flags: "i".into(),
+            lazy: true
+          })
+        ),
+        (
+          "./dir/**/*",
+          Some(&GlobOptions::RequireContext {
+            directory: "./dir".into(),
+            recursive: true,
+            regexp: "^\\.\\/.*$".into(),
+            flags: "".into(),
+            lazy: false
+          })
+        ),
+        (
+          "./locales/*.json",
+          Some(&GlobOptions::Lookup { lazy: true })
+        ),
+        ("./icons/*", Some(&GlobOptions::Lookup { lazy: false })),
+        ("./flags/*.svg", Some(&GlobOptions::Url)),
+      ]
+    );
+
+    // The same pattern with different options gets a different module.
+    assert_ne!(
+      result.dependencies[0].placeholder,
+      result.dependencies[1].placeholder
+    );
+    // Each call is replaced with a require of the generated module, and lookups pass
+    // the original specifier to it.
+    let code = std::str::from_utf8(&result.code).unwrap();
+    let require = |i: usize| {
+      format!(
+        "require(\"{}\")",
+        result.dependencies[i].placeholder.as_ref().unwrap()
+      )
+    };
+    assert!(code.contains(&format!("const modules = {};", require(0))));
+    assert!(code.contains(&format!("const all = {};", require(3))));
+    assert!(code.contains(&format!(
+      "const locale = {}(`./locales/${{lang}}.json`);",
+      require(4)
+    )));
+    assert!(code.contains(&format!("const icon = {}('./icons/' + name);", require(5))));
+    assert!(code.contains(&format!(
+      "const flag = {}(`./flags/${{code}}.svg`);",
+      require(6)
+    )));
+    // A static path is a regular URL dependency.
+    let url = result.dependencies.last().unwrap();
+    assert_eq!(url.kind, DependencyKind::Url);
+    assert_eq!(&*url.specifier, "./flags/fr.svg");
+
+    // Without the glob resolver, no dependency is created for partially dynamic specifiers.
+    let result = transform_code(
+      "const locale = import(`./locales/${lang}.json`);\nconst icon = require('./icons/' + name);",
+      Config::default(),
+    );
+    assert!(result.dependencies.is_empty());
+    let code = std::str::from_utf8(&result.code).unwrap();
+    assert!(code.contains("const locale = require(`./locales/${lang}.json`);"));
+    assert!(code.contains("require('./icons/' + name)"));
+    let warnings: Vec<_> = result
+      .diagnostics
+      .unwrap()
+      .into_iter()
+      .map(|diagnostic| (diagnostic.message, diagnostic.severity))
+      .collect();
+    assert_eq!(
+      warnings,
+      vec![
+        (
+          "`import()` with a partially dynamic specifier is not bundled.".into(),
+          DiagnosticSeverity::Warning
+        ),
+        (
+          "`require()` with a partially dynamic specifier is not bundled.".into(),
+          DiagnosticSeverity::Warning
+        ),
+      ]
+    );
+
+    let errors = |code: &str| -> Vec<String> {
+      transform_code(code, Config::default())
+        .diagnostics
+        .unwrap_or_default()
+        .into_iter()
+        .map(|diagnostic| diagnostic.message)
+        .collect()
+    };
+    assert_eq!(
+      errors("import.meta.glob(pattern);"),
+      vec!["import.meta.glob() only supports a string literal pattern."]
+    );
+    assert_eq!(
+      errors("import.meta.glob('./*.js', { eager: isEager });"),
+      vec!["Only static `eager` and `import` options are supported by import.meta.glob()."]
+    );
+    assert_eq!(
+      errors("require.context('./dir', true, /x/, 'weak');"),
+      vec!["Only the \"sync\" and \"lazy\" modes are supported by require.context()."]
+    );
+    assert_eq!(
+      errors("require.context(dir);"),
+      vec!["require.context() only supports static arguments."]
+    );
+    assert_eq!(
+      errors("require.context();"),
+      vec!["require.context() requires a directory."]
+    );
+  }
+
+  #[test]
+  fn magic_comments() {
+    let code = "import(/* webpackChunkName: \"editor\" */ /* webpackPrefetch: true */ './editor');\nimport(/* webpackChunkName: 'a, b', webpackPreload: 1, webpackMode: \"eager\" */ './eager');\nimport(/* webpackPrefetch: 0, webpackMode: \"lazy-once\" */ './lazy');\nimport(/* webpackChunkName: name */ './other', { prefetch: true });";
+    let result = transform_code(code, Config::default());
+    let comments: Vec<_> = result
+      .dependencies
+      .iter()
+      .map(|dep| {
+        (
+          &*dep.specifier,
+          dep.chunk_name.as_deref(),
+          dep.resource_hint,
+          dep.import_mode,
+        )
+      })
+      .collect();
+    assert_eq!(
+      comments,
+      vec![
+        (
+          "./editor",
+          Some("editor"),
+          Some(ResourceHint::Prefetch),
+          None
+        ),
+        (
+          "./eager",
+          Some("a, b"),
+          Some(ResourceHint::Preload),
+          Some(ImportMode::Eager)
+        ),
+        ("./lazy", None, None, Some(ImportMode::Lazy)),
+        // Only string chunk names are supported, and the option is used without a comment.
+        ("./other", None, Some(ResourceHint::Prefetch), None),
+      ]
+    );
+  }
+
+  #[test]
+  fn ignore_comments() {
+    for comment in &[
+      "/* @parcel-ignore */",
+      "/* webpackIgnore: true */",
+      "/* @vite-ignore */",
+    ] {
+      let code = format!(
+        "export const a = () => import({0} './a');\nexport const b = () => import({0} url);",
+        comment
+      );
+      for scope_hoist in [false, true] {
+        let result = transform_code(
+          &code,
+          Config {
+            module_id: "abc".into(),
+            scope_hoist,
+            trace_bailouts: true,
+            ..Default::default()
+          },
+        );
+        // No dependency or NonStaticDynamicImport bailout, and the import is left native.
+        assert!(result.dependencies.is_empty(), "{}", comment);
+        assert!(result.diagnostics.is_none(), "{}", comment);
+        let output = String::from_utf8(result.code).unwrap();
+        assert!(
+          output.contains(&format!("import({} './a')", comment)),
+          "{}",
+          output
+        );
+        assert!(
+          output.contains(&format!("import({} url)", comment)),
+          "{}",
+          output
+        );
+      }
+    }
+
+    // Other comments are not ignored.
+    let result = transform_code(
+      "import(/* webpackIgnore: false */ './a');",
+      Config::default(),
+    );
+    assert_eq!(result.dependencies.len(), 1);
+  }
+
+  #[test]
+  fn import_meta_resolve() {
+    let code = "export const url = import.meta.resolve('./worker.js');";
+    let resolve = |is_browser, is_library| {
+      let result = transform_code(
+        code,
+        Config {
+          is_browser,
+          is_library,
+          is_esm_output: is_library,
+          ..Default::default()
+        },
+      );
+      assert!(result.diagnostics.is_none());
+      (result.dependencies, String::from_utf8(result.code).unwrap())
+    };
+
+    // Apps get the URL from a runtime.
+    let (deps, output) = resolve(true, false);
+    assert_eq!(deps.len(), 1);
+    assert_eq!(deps[0].kind, DependencyKind::Url);
+    assert_eq!(&*deps[0].specifier, "./worker.js");
+    let placeholder = deps[0].placeholder.as_ref().unwrap();
+    assert!(output.contains(&format!("require(\"{}\")", placeholder)));
+
+    // Browser libraries get a statically analyzable URL, as a string like the native result.
+    let (deps, output) = resolve(true, true);
+    assert_eq!(deps.len(), 1);
+    let placeholder = deps[0].placeholder.as_ref().unwrap();
+    assert!(output.contains(&format!(
+      "new URL(\"{}\", import.meta.url).href",
+      placeholder
+    )));
+
+    // Node supports it natively in ES modules.
+    let (deps, output) = resolve(false, true);
+    assert!(deps.is_empty());
+    assert!(output.contains("import.meta.resolve('./worker.js')"));
+  }
+
+  #[test]
+  fn create_require() {
+    let code = "import {createRequire} from 'module';\nconst require = createRequire(import.meta.url);\nconst data = require('./data.json');\nconst dynamic = require(name);\nlet other = createRequire(import.meta.url);\nother('./other.js');\nexport default data;";
+    let config = |scope_hoist| Config {
+      scope_hoist,
+      ..Config::new("index.js", code)
+    };
+    let requires = |result: &TransformResult| -> Vec<String> {
+      result
+        .dependencies
+        .iter()
+        .filter(|dep| dep.kind == DependencyKind::Require)
+        .map(|dep| dep.specifier.to_string())
+        .collect()
+    };
+
+    let warnings = |result: &TransformResult| -> Vec<String> {
+      result
+        .diagnostics
+        .iter()
+        .flatten()
+        .map(|diagnostic| {
+          assert_eq!(diagnostic.severity, DiagnosticSeverity::Warning);
+          diagnostic.message.clone()
+        })
+        .collect()
+    };
+    let warning = "Calls to `other` are not bundled, because it is declared with `let`.";
+
+    // Static requires through a const binding are bundled, and other calls are left to Node.
+    let result = transform(config(false)).unwrap();
+    assert_eq!(requires(&result), vec!["./data.json"]);
+    assert_eq!(warnings(&result), vec![warning]);
+    let output = std::str::from_utf8(&result.code).unwrap();
+    assert!(output.contains("const data = require('./data.json');"));
+    assert!(output.contains("const dynamic = require1(name);"));
+    assert!(output.contains("other('./other.js');"));
+
+    // The calls are regular requires when hoisting, not calls to the renamed binding.
+    let result = transform(config(true)).unwrap();
+    assert_eq!(requires(&result), vec!["./data.json"]);
+    assert_eq!(warnings(&result), vec![warning]);
+    let output = std::str::from_utf8(&result.code).unwrap();
+    assert!(output.contains("import \":./data.json\";"));
+    assert!(!output.contains("$$var$require('./data.json')"));
+    assert!(output.contains("const $$var$dynamic = $$var$require(name);"));
+    assert!(output.contains("$$var$other('./other.js');"));
+  }
+
+  #[test]
+  fn require_resolve() {
+    let code = "import {createRequire} from 'module';\nconst req = createRequire(import.meta.url);\nexport const worker = require.resolve('./worker.js');\nexport const other = req.resolve('./other.js');\nexport const paths = require.resolve.paths('lodash');";
+    for scope_hoist in [false, true] {
+      let result = transform(Config {
+        scope_hoist,
+        ..Config::new("index.js", code)
+      })
+      .unwrap();
+      assert!(result.diagnostics.is_none());
+      let deps: Vec<_> = result
+        .dependencies
+        .iter()
+        .filter(|dep| dep.kind == DependencyKind::RequireResolve)
+        .collect();
+      assert_eq!(
+        deps.iter().map(|dep| &*dep.specifier).collect::<Vec<_>>(),
+        vec!["./worker.js", "./other.js"]
+      );
+
+      // Each call is replaced with a placeholder for the id of the module, rather than a
+      // require that would execute it.
+      let output = std::str::from_utf8(&result.code).unwrap();
+      for dep in deps {
+        let placeholder = dep.placeholder.as_ref().unwrap();
+        assert!(output.contains(&format!("\"{}\"", placeholder)));
+        assert!(!output.contains(&format!("require(\"{}\")", placeholder)));
+      }
+      // require.resolve.paths() is left to Node.
+      assert!(output.contains("require.resolve.paths('lodash')"));
+    }
+
+    // A module required by its id is required like any other, using the placeholder.
+    let code = "const a = require(require.resolve('./a'));\noutput = a;";
+    for scope_hoist in [false, true] {
+      let result = transform(Config {
+        scope_hoist,
+        module_id: "abc".into(),
+        ..Config::new("index.js", code)
+      })
+      .unwrap();
+      assert!(result.diagnostics.is_none());
+      assert_eq!(result.dependencies.len(), 1);
+      let dep = &result.dependencies[0];
+      assert_eq!(dep.kind, DependencyKind::RequireResolve);
+      let placeholder = dep.placeholder.as_ref().unwrap();
+      let output = std::str::from_utf8(&result.code).unwrap();
+      if scope_hoist {
+        assert!(output.contains(&format!("import \"abc:{}\";", placeholder)));
+      } else {
+        assert!(output.contains(&format!("const a = require(\"{}\");", placeholder)));
+      }
+    }
+  }
+
+  #[test]
+  fn legal_comments() {
+    let code =
+      "/*! Copyright A */\nlet a = 1;\n// normal comment\nfoo(a);\n/** @license B */\nbar();\n";
+    let transform_legal = |legal_comments, minify| {
+      let result = transform_code(
+        code,
+        Config {
+          legal_comments,
+          minify,
+          source_maps: true,
+          ..Default::default()
+        },
+      );
+      let texts: Vec<&str> = result
+        .legal_comments
+        .iter()
+        .map(|comment| comment.text.as_str())
+        .collect();
+      assert_eq!(texts, vec!["/*! Copyright A */", "/** @license B */"]);
+      let map = sourcemap::SourceMap::from_slice(result.map.unwrap().as_bytes()).unwrap();
+      (String::from_utf8(result.code).unwrap(), map)
+    };
+    // Returns the original line of the first mapping of the generated line.
+    let original_line = |map: &sourcemap::SourceMap, line| {
+      map
+        .tokens()
+        .find(|token| token.get_dst_line() == line)
+        .map(|token| token.get_src_line())
+    };
+
+    let (output, _) = transform_legal(LegalComments::Inline, false);
+    assert_eq!(
+      output,
+      "/*! Copyright A */ let a = 1;\n// normal comment\nfoo(a);\n/** @license B */ bar();\n"
+    );
+    let (output, _) = transform_legal(LegalComments::Inline, true);
+    assert_eq!(
+      output,
+      "/*! Copyright A */ let a=1;foo(a),/** @license B */ bar();"
+    );
+
+    // The source map is offset by the lines of the header.
+    let (output, map) = transform_legal(LegalComments::Top, false);
+    assert_eq!(
+      output,
+      "/*! Copyright A */\n/** @license B */\nlet a = 1;\n// normal comment\nfoo(a);\nbar();\n"
+    );
+    assert_eq!(original_line(&map, 2), Some(1));
+    assert_eq!(original_line(&map, 4), Some(3));
+    let (output, map) = transform_legal(LegalComments::Top, true);
+    assert_eq!(
+      output,
+      "/*! Copyright A */\n/** @license B */\nlet a=1;foo(a),bar();"
+    );
+    assert_eq!(original_line(&map, 0), None);
+    assert_eq!(original_line(&map, 2), Some(1));
+
+    let (output, _) = transform_legal(LegalComments::Strip, false);
+    assert_eq!(output, "let a = 1;\n// normal comment\nfoo(a);\nbar();\n");
+    let (output, _) = transform_legal(LegalComments::Strip, true);
+    assert_eq!(output, "let a=1;foo(a),bar();");
+  }
+
+  #[test]
+  fn standard_decorators() {
+    let config = || Config {
+      decorators: true,
+      decorators_version: DecoratorsVersion::Standard,
+      ..Default::default()
+    };
+    let result = transform_code(
+      "class A {\n  static accessor x = 1;\n  @dec accessor #y;\n}",
+      config(),
+    );
+    assert!(result.diagnostics.is_none());
+    let output = String::from_utf8(result.code).unwrap();
+    assert!(output.contains("static get x() {\n        return this.#_x;\n    }"));
+    assert!(output.contains("static #_x = 1;"));
+    assert!(output.contains("get #y() {\n        return _get_y.call(this);\n    }"));
+
+    // Decorators on private getters, setters and accessors are not supported when class
+    // fields are compiled, but private fields and methods are.
+    let result = transform_code(
+      "class A {\n  @dec #x;\n  @dec #m() {}\n  @dec get #g() {}\n  @dec accessor #y;\n}",
+      Config {
+        targets: Some(Targets::Query("chrome 60".into())),
+        ..config()
+      },
+    );
+    let diagnostics = result.diagnostics.unwrap();
+    let lines: Vec<usize> = diagnostics
+      .iter()
+      .map(|diagnostic| {
+        diagnostic.code_highlights.as_ref().unwrap()[0]
+          .loc
+          .start_line
+      })
+      .collect();
+    assert_eq!(lines, vec![4, 5]);
+    assert_eq!(
+      diagnostics[0].message,
+      "Decorators on private getters, setters and accessors are not supported when class fields are compiled for the targets."
+    );
+  }
+
+  #[test]
+  fn decorator_metadata() {
+    let result = transform_code(
+      "import {Service} from './service';\nclass A {\n  @dec service: Service;\n}",
+      Config {
+        filename: "index.ts".into(),
+        is_type_script: true,
+        decorators: true,
+        emit_decorator_metadata: true,
+        ..Default::default()
+      },
+    );
+    assert!(result.diagnostics.is_none());
+    let output = String::from_utf8(result.code).unwrap();
+    // The import is referenced by the metadata, so it is kept when types are stripped.
+    assert!(output.contains("require(\"./service\")"));
+    assert!(
+      output.contains("typeof _service.Service === \"undefined\" ? Object : _service.Service")
+    );
+  }
+
+  #[test]
+  fn preserve_esm() {
+    let result = transform_code(
+      "import x, {y} from './a';\nexport * from './b';\nexport {z} from './c';\nimport('./d');\nexport default x + y;",
+      Config {
+        preserve_esm: true,
+        ..Default::default()
+      },
+    );
+    let placeholders: Vec<_> = result
+      .dependencies
+      .iter()
+      .map(|dep| dep.placeholder.as_ref().unwrap())
+      .collect();
+    assert_eq!(placeholders.len(), 4);
+    assert_eq!(
+      String::from_utf8(result.code).unwrap(),
+      format!(
+        "import x, {{ y }} from \"{}\";\nexport * from \"{}\";\nexport {{ z }} from \"{}\";\nimport(\"{}\");\nexport default x + y;\n",
+        placeholders[0], placeholders[1], placeholders[2], placeholders[3]
+      )
+    );
+    assert!(!result.needs_esm_helpers);
+    assert!(result.preserved_esm);
+
+    // CommonJS modules have no import or export statements to keep.
+    let result = transform_code(
+      "const a = require('./a');\nimport('./b');\nmodule.exports = a;",
+      Config {
+        preserve_esm: true,
+        ..Default::default()
+      },
+    );
+    assert!(!result.preserved_esm);
+    let output = String::from_utf8(result.code).unwrap();
+    assert!(output.contains("const a = require('./a');"));
+    assert!(output.contains(&format!(
+      "require(\"{}\")",
+      result.dependencies[1].placeholder.as_ref().unwrap()
+    )));
+    assert!(!output.contains("import("));
+
+    // Scope hoisting takes precedence.
+    let result = transform_code(
+      "import x from './a';",
+      Config {
+        preserve_esm: true,
+        scope_hoist: true,
+        ..Default::default()
+      },
+    );
+    assert!(result.dependencies[0].placeholder.is_none());
+  }
+}
@@ -0,0 +1,158 @@
+use swc_common::{sync::Lrc, Mark, SourceMap};
+use swc_ecmascript::ast::Module;
+
+use crate::utils::Diagnostic;
+
+/// A point in the transform pipeline where custom passes run.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Stage {
+  /// Directly after parsing, before JSX, decorators and TypeScript are compiled.
+  /// Identifiers have not been resolved yet, so `global_mark` is not applied.
+  AfterParse,
+  /// After TypeScript types are stripped and identifiers are resolved.
+  AfterTypeScript,
+  /// After env/fs inlining, globals and preset_env, before dependencies are collected.
+  BeforeDependencies,
+  /// After scope hoisting, or after the conversion to CommonJS when scope hoisting is disabled.
+  AfterHoist,
+}
+
+/// State of the transform available to custom passes.
+pub struct PassContext<'a> {
+  pub filename: &'a str,
+  pub source_map: &'a Lrc<SourceMap>,
+  /// The mark of unresolved (global) identifiers.
+  pub global_mark: Mark,
+  /// Diagnostics are returned in the transform result. Errors stop the
+  /// transform at the next checkpoint, which is after dependency collection
+  /// for the earlier stages, and before codegen for `AfterHoist`.
+  pub diagnostics: &'a mut Vec<Diagnostic>,
+}
+
+/// A custom transform, usually running a `Fold` over the module:
+///
+/// ```ignore
+/// impl Pass for MyPass {
+///   fn run(&self, module: Module, ctx: &mut PassContext) -> Module {
+///     module.fold_with(&mut MyFold::new(ctx.global_mark))
+///   }
+/// }
+/// ```
+pub trait Pass: Send + Sync {
+  fn run(&self, module: Module, ctx: &mut PassContext) -> Module;
+}
+
+impl<F> Pass for F
+where
+  F: Fn(Module, &mut PassContext) -> Module + Send + Sync,
+{
+  fn run(&self, module: Module, ctx: &mut PassContext) -> Module {
+    self(module, ctx)
+  }
+}
+
+/// Custom passes to run during `transform_with_passes`. Passes registered for
+/// the same stage run in the order they were registered.
+#[derive(Default)]
+pub struct PassRegistry {
+  passes: Vec<(Stage, Box<dyn Pass>)>,
+}
+
+impl PassRegistry {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  pub fn register<P: Pass + 'static>(&mut self, stage: Stage, pass: P) -> &mut Self {
+    self.passes.push((stage, Box::new(pass)));
+    self
+  }
+
+  pub(crate) fn run(&self, stage: Stage, module: Module, ctx: &mut PassContext) -> Module {
+    self
+      .passes
+      .iter()
+      .filter(|(s, _)| *s == stage)
+      .fold(module, |module, (_, pass)| pass.run(module, ctx))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::utils::{create_require, DiagnosticSeverity};
+  use crate::{transform_with_passes, Config, DependencyKind};
+  use std::sync::{Arc, Mutex};
+  use swc_common::DUMMY_SP;
+  use swc_ecmascript::ast::{Expr, ExprStmt, ModuleItem, Stmt};
+
+  #[test]
+  fn runs_passes_at_each_stage() {
+    let stages = Arc::new(Mutex::new(vec![]));
+    let mut passes = PassRegistry::new();
+    for stage in [
+      Stage::AfterHoist,
+      Stage::BeforeDependencies,
+      Stage::AfterTypeScript,
+      Stage::AfterParse,
+    ] {
+      let stages = stages.clone();
+      passes.register(stage, move |module: Module, _ctx: &mut PassContext| {
+        stages.lock().unwrap().push(stage);
+        module
+      });
+    }
+
+    // Dependencies added before the dependency collector are collected.
+    passes.register(
+      Stage::BeforeDependencies,
+      |mut module: Module, _ctx: &mut PassContext| {
+        module.body.push(ModuleItem::Stmt(Stmt::Expr(ExprStmt {
+          span: DUMMY_SP,
+          expr: Box::new(Expr::Call(create_require("./injected".into()))),
+        })));
+        module
+      },
+    );
+
+    passes.register(
+      Stage::AfterHoist,
+      |module: Module, ctx: &mut PassContext| {
+        ctx.diagnostics.push(Diagnostic {
+          message: format!("Checked {}", ctx.filename),
+          code_highlights: None,
+          hints: None,
+          show_environment: false,
+          severity: DiagnosticSeverity::Warning,
+          documentation_url: None,
+        });
+        module
+      },
+    );
+
+    let config = Config {
+      is_type_script: true,
+      ..Config::new("index.ts", "let x: number = 1;")
+    };
+    let result = transform_with_passes(config, &passes).unwrap();
+
+    assert_eq!(
+      *stages.lock().unwrap(),
+      vec![
+        Stage::AfterParse,
+        Stage::AfterTypeScript,
+        Stage::BeforeDependencies,
+        Stage::AfterHoist
+      ]
+    );
+    assert_eq!(result.dependencies.len(), 1);
+    assert_eq!(result.dependencies[0].kind, DependencyKind::Require);
+    assert_eq!(&*result.dependencies[0].specifier, "./injected");
+    let diagnostics = result.diagnostics.unwrap();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].message, "Checked index.ts");
+    let code = String::from_utf8(result.code).unwrap();
+    assert!(code.contains("let x = 1;"));
+    assert!(code.contains("require(\"./injected\");"));
+  }
+}
@@ -0,0 +1,63 @@
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+use swc_common::{Span, DUMMY_SP};
+use swc_ecmascript::ast;
+use swc_ecmascript::visit::{Node, Visit, VisitWith};
+
+/// Wall time of a stage of the transform pipeline, and the size of the module after it.
+#[derive(Serialize, Debug, Deserialize)]
+pub struct PassProfile {
+  pub name: String,
+  pub duration_ms: f64,
+  pub node_count: usize,
+}
+
+/// Records a `PassProfile` for each stage of the pipeline when profiling is enabled.
+pub struct Profiler {
+  start: Option<Instant>,
+}
+
+impl Profiler {
+  pub fn new(enabled: bool) -> Self {
+    Profiler {
+      start: if enabled { Some(Instant::now()) } else { None },
+    }
+  }
+
+  /// Records the time since the previous stage ended. Counting the nodes of the module
+  /// is not included in the time of either stage.
+  pub fn record(
+    &mut self,
+    profile: &mut Option<Vec<PassProfile>>,
+    name: &str,
+    module: &ast::Module,
+  ) {
+    if let Some(start) = self.start {
+      let duration = start.elapsed();
+      profile.get_or_insert_with(Vec::new).push(PassProfile {
+        name: name.into(),
+        duration_ms: duration.as_secs_f64() * 1000.0,
+        node_count: count_nodes(module),
+      });
+      self.start = Some(Instant::now());
+    }
+  }
+}
+
+/// Approximates the number of AST nodes by counting spans, which nearly every node has.
+fn count_nodes(module: &ast::Module) -> usize {
+  let mut counter = NodeCounter { count: 0 };
+  module.visit_with(&ast::Invalid { span: DUMMY_SP } as _, &mut counter);
+  counter.count
+}
+
+struct NodeCounter {
+  count: usize,
+}
+
+impl Visit for NodeCounter {
+  fn visit_span(&mut self, _node: &Span, _parent: &dyn Node) {
+    self.count += 1;
+  }
+}
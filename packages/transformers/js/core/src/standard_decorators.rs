@@ -0,0 +1,1298 @@
+use std::collections::HashSet;
+
+use swc_atoms::JsWord;
+use swc_common::{BytePos, Mark, SourceMap, Span, Spanned, DUMMY_SP};
+use swc_ecmascript::ast::*;
+use swc_ecmascript::utils::constructor::inject_after_super;
+use swc_ecmascript::visit::{Fold, FoldWith, Node, Visit, VisitWith};
+
+use crate::utils::{CodeHighlight, Diagnostic, DiagnosticSeverity, SourceLocation};
+
+const HELPERS: &str = "@parcel/transformer-js/src/decorator-helpers.js";
+
+// Member kinds, matching decorator-helpers.js.
+const FIELD: f64 = 0.0;
+const ACCESSOR: f64 = 1.0;
+const METHOD: f64 = 2.0;
+const GETTER: f64 = 3.0;
+const SETTER: f64 = 4.0;
+const STATIC: f64 = 5.0;
+
+/// This pass compiles standard (2022-03) decorators. Classes with decorators call the
+/// `applyDecs` runtime helper from a private static field, which is initialized before
+/// any other static field. It returns the initializers of decorated fields, and the
+/// decorated class if it has class decorators, which are assigned to temporary variables:
+///
+/// ```js
+/// @dec class Foo { @field x = 1; }
+/// ```
+///
+/// becomes
+///
+/// ```js
+/// class Foo {
+///   static #_ = [_init_x, _class, _initClass] = _applyDecs(this, [[[field], 0, "x"]], [dec]);
+///   x = _init_x(this, 1);
+/// }
+/// Foo = _class, _initClass();
+/// ```
+///
+/// Decorator expressions and computed keys of decorated members are evaluated before the class.
+///
+/// `accessor` fields are stored in a private field, e.g. `accessor x = 1` becomes
+/// `#_x = 1; get x() { return this.#_x; } set x(v) { this.#_x = v; }`. The parser does not
+/// support them, so `accessors` has the start of the name of each one (see `strip_accessors`).
+///
+/// When class fields are compiled for the targets, the class is referenced by name rather than
+/// `this`, because SWC's class properties transform does not handle `this` or private names in
+/// static initializers. The decorators of private members are returned from a temporary static
+/// method instead. Decorators on private getters, setters and accessors are not supported, because
+/// the transform does not support private getters.
+pub fn standard_decorators<'a>(
+  source_map: &'a SourceMap,
+  diagnostics: &'a mut Vec<Diagnostic>,
+  lower_class_fields: bool,
+  accessors: &'a [BytePos],
+) -> impl Fold + 'a {
+  StandardDecorators {
+    source_map,
+    diagnostics,
+    lower_class_fields,
+    accessors,
+    vars: vec![],
+    apply_decs: None,
+  }
+}
+
+struct DecoratedClass {
+  /// Expressions to evaluate before the class.
+  exprs: Vec<Expr>,
+  class: Class,
+  /// The variables assigned the decorated class and its initializers, if there are class decorators.
+  class_result: Option<(Ident, Ident)>,
+}
+
+/// An `accessor` field, which is parsed as a field without the keyword.
+struct Accessor {
+  span: Span,
+  key: AccessorKey,
+  value: Option<Box<Expr>>,
+  is_static: bool,
+  decorators: Vec<Decorator>,
+}
+
+enum AccessorKey {
+  Public(PropName),
+  Private(PrivateName),
+}
+
+struct StandardDecorators<'a> {
+  source_map: &'a SourceMap,
+  diagnostics: &'a mut Vec<Diagnostic>,
+  lower_class_fields: bool,
+  accessors: &'a [BytePos],
+  /// Temporary variables of the current function, declared at the top of its body.
+  vars: Vec<Ident>,
+  apply_decs: Option<Ident>,
+}
+
+impl<'a> Fold for StandardDecorators<'a> {
+  fn fold_module(&mut self, node: Module) -> Module {
+    let mut node = node.fold_children_with(self);
+
+    if !self.vars.is_empty() {
+      let decl = create_var_decl(std::mem::take(&mut self.vars));
+      node.body.insert(0, ModuleItem::Stmt(decl));
+    }
+
+    if let Some(apply_decs) = self.apply_decs.take() {
+      node.body.insert(
+        0,
+        ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
+          span: DUMMY_SP,
+          specifiers: vec![ImportSpecifier::Named(ImportNamedSpecifier {
+            span: DUMMY_SP,
+            local: apply_decs,
+            imported: Some(Ident::new("applyDecs".into(), DUMMY_SP)),
+          })],
+          src: Str {
+            span: DUMMY_SP,
+            value: HELPERS.into(),
+            has_escape: false,
+            kind: StrKind::Synthesized,
+          },
+          type_only: false,
+          asserts: None,
+        })),
+      );
+    }
+
+    node
+  }
+
+  fn fold_module_items(&mut self, items: Vec<ModuleItem>) -> Vec<ModuleItem> {
+    let mut result = Vec::with_capacity(items.len());
+    for item in items {
+      match item {
+        ModuleItem::Stmt(Stmt::Decl(Decl::Class(decl))) if has_decorators(&decl.class) => {
+          let (before, decl, after) = self.transform_class_decl(decl);
+          result.extend(before.map(ModuleItem::Stmt));
+          result.push(ModuleItem::Stmt(Stmt::Decl(Decl::Class(decl))));
+          result.extend(after.map(ModuleItem::Stmt));
+        }
+        ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl {
+          decl: Decl::Class(decl),
+          span,
+        }))
+          if has_decorators(&decl.class) =>
+        {
+          let (before, decl, after) = self.transform_class_decl(decl);
+          result.extend(before.map(ModuleItem::Stmt));
+          result.push(ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl {
+            decl: Decl::Class(decl),
+            span,
+          })));
+          result.extend(after.map(ModuleItem::Stmt));
+        }
+        // `export default @dec class {}` is split into a variable and an export, because
+        // the default export would not be updated when the class is replaced.
+        ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultDecl(ExportDefaultDecl {
+          decl: DefaultDecl::Class(class),
+          span,
+        }))
+          if has_decorators(&class.class) =>
+        {
+          let ident = class
+            .ident
+            .clone()
+            .unwrap_or_else(|| private_ident("_default"));
+          let init = self.transform_class_expr(class.ident, class.class);
+          result.push(ModuleItem::Stmt(create_let_decl(ident.clone(), init)));
+          result.push(ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(
+            NamedExport {
+              span,
+              specifiers: vec![ExportSpecifier::Named(ExportNamedSpecifier {
+                span: DUMMY_SP,
+                orig: ident,
+                exported: Some(Ident::new("default".into(), DUMMY_SP)),
+              })],
+              src: None,
+              type_only: false,
+              asserts: None,
+            },
+          )));
+        }
+        item => result.push(item.fold_with(self)),
+      }
+    }
+    result
+  }
+
+  fn fold_stmts(&mut self, stmts: Vec<Stmt>) -> Vec<Stmt> {
+    let mut result = Vec::with_capacity(stmts.len());
+    for stmt in stmts {
+      match stmt {
+        Stmt::Decl(Decl::Class(decl)) if has_decorators(&decl.class) => {
+          let (before, decl, after) = self.transform_class_decl(decl);
+          result.extend(before);
+          result.push(Stmt::Decl(Decl::Class(decl)));
+          result.extend(after);
+        }
+        stmt => result.push(stmt.fold_with(self)),
+      }
+    }
+    result
+  }
+
+  fn fold_class(&mut self, node: Class) -> Class {
+    let mut node = node.fold_children_with(self);
+    if !self.accessors.is_empty() {
+      let mut names = private_names(&node);
+      for member in std::mem::take(&mut node.body) {
+        if self.is_accessor(&member) {
+          let members = self.lower_accessor(into_accessor(member), &mut names);
+          node.body.extend(members);
+        } else {
+          node.body.push(member);
+        }
+      }
+    }
+    node
+  }
+
+  fn fold_expr(&mut self, node: Expr) -> Expr {
+    match node {
+      Expr::Class(class) if has_decorators(&class.class) => {
+        self.transform_class_expr(class.ident, class.class)
+      }
+      node => node.fold_children_with(self),
+    }
+  }
+
+  fn fold_function(&mut self, node: Function) -> Function {
+    let vars = std::mem::take(&mut self.vars);
+    let mut node = node.fold_children_with(self);
+    let vars = std::mem::replace(&mut self.vars, vars);
+    if !vars.is_empty() {
+      if let Some(body) = &mut node.body {
+        body.stmts.insert(0, create_var_decl(vars));
+      }
+    }
+    node
+  }
+
+  fn fold_arrow_expr(&mut self, node: ArrowExpr) -> ArrowExpr {
+    let vars = std::mem::take(&mut self.vars);
+    let mut node = node.fold_children_with(self);
+    let vars = std::mem::replace(&mut self.vars, vars);
+    if !vars.is_empty() {
+      let mut stmts = vec![create_var_decl(vars)];
+      match node.body {
+        BlockStmtOrExpr::BlockStmt(block) => stmts.extend(block.stmts),
+        BlockStmtOrExpr::Expr(expr) => stmts.push(Stmt::Return(ReturnStmt {
+          span: DUMMY_SP,
+          arg: Some(expr),
+        })),
+      }
+      node.body = BlockStmtOrExpr::BlockStmt(BlockStmt {
+        span: DUMMY_SP,
+        stmts,
+      });
+    }
+    node
+  }
+}
+
+impl<'a> StandardDecorators<'a> {
+  fn transform_class(&mut self, ident: Option<&Ident>, class: Class) -> DecoratedClass {
+    let mut class = class.fold_children_with(self);
+    let receiver = match ident {
+      Some(ident) if self.lower_class_fields => Expr::Ident(ident.clone()),
+      _ => Expr::This(ThisExpr { span: DUMMY_SP }),
+    };
+    // The TypeScript pass moves static fields out of the class without replacing `this`.
+    let static_receiver = match ident {
+      Some(ident) => Expr::Ident(ident.clone()),
+      None => Expr::This(ThisExpr { span: DUMMY_SP }),
+    };
+
+    // Expressions evaluated before the class.
+    let mut exprs = vec![];
+    // Elements of the array returned by applyDecs.
+    let mut targets = vec![];
+    let mut member_decs = vec![];
+    let mut has_proto = false;
+    let mut has_static = false;
+    let mut has_private = false;
+    let mut names = private_names(&class);
+
+    let class_decs = self.hoist_decorators(std::mem::take(&mut class.decorators), &mut exprs);
+
+    for mut member in std::mem::take(&mut class.body) {
+      if self.is_accessor(&member) {
+        let accessor = into_accessor(member);
+        if accessor.decorators.is_empty() {
+          let members = self.lower_accessor(accessor, &mut names);
+          class.body.extend(members);
+          continue;
+        }
+
+        let decs = self.hoist_decorators(accessor.decorators, &mut exprs);
+        if accessor.is_static {
+          has_static = true;
+        } else {
+          has_proto = true;
+        }
+        let this = if accessor.is_static {
+          static_receiver.clone()
+        } else {
+          Expr::This(ThisExpr { span: DUMMY_SP })
+        };
+
+        match accessor.key {
+          AccessorKey::Public(mut key) => {
+            let name = self.hoist_key(&mut key, &mut exprs);
+            let init = self.create_temp(&format!("_init_{}", temp_name(&name)));
+            targets.push(init.clone());
+            let storage = storage_name(&mut names, &temp_name(&name));
+            let value = create_field_init(&init, this, accessor.value);
+            class.body.extend(create_accessor_members(
+              key.clone(),
+              key,
+              storage,
+              Some(Box::new(value)),
+              accessor.is_static,
+            ));
+            member_decs.push(create_array(vec![
+              Expr::Array(decs),
+              create_kind(ACCESSOR, accessor.is_static),
+              name,
+            ]));
+          }
+          AccessorKey::Private(key) => {
+            self.check_private_accessor(accessor.span);
+            has_private = true;
+            let name = &key.id.sym;
+            let init = self.create_temp(&format!("_init_{}", name));
+            let get = self.create_temp(&format!("_get_{}", name));
+            let set = self.create_temp(&format!("_set_{}", name));
+            targets.extend([init.clone(), get.clone(), set.clone()]);
+
+            // The decorated get and set functions are returned by applyDecs, and
+            // the private accessor calls them.
+            let storage = storage_name(&mut names, name);
+            class.body.push(create_private_prop(
+              storage.clone(),
+              Some(Box::new(create_field_init(&init, this, accessor.value))),
+              accessor.is_static,
+            ));
+            class.body.push(create_private_method(
+              key.clone(),
+              create_private_accessor_fn(&get, MethodKind::Getter),
+              MethodKind::Getter,
+              accessor.is_static,
+            ));
+            class.body.push(create_private_method(
+              key.clone(),
+              create_private_accessor_fn(&set, MethodKind::Setter),
+              MethodKind::Setter,
+              accessor.is_static,
+            ));
+            member_decs.push(create_array(vec![
+              Expr::Array(decs),
+              create_kind(ACCESSOR, accessor.is_static),
+              create_str(name),
+              create_fn_expr(create_private_getter(&storage)),
+              create_fn_expr(create_private_setter(&storage)),
+            ]));
+          }
+        }
+        continue;
+      }
+
+      match &mut member {
+        ClassMember::Method(method) if !method.function.decorators.is_empty() => {
+          let decs = std::mem::take(&mut method.function.decorators);
+          let decs = self.hoist_decorators(decs, &mut exprs);
+          let name = self.hoist_key(&mut method.key, &mut exprs);
+          let kind = method_kind(method.kind);
+          if method.is_static {
+            has_static = true;
+          } else {
+            has_proto = true;
+          }
+          member_decs.push(create_array(vec![
+            Expr::Array(decs),
+            create_kind(kind, method.is_static),
+            name,
+          ]));
+        }
+        ClassMember::PrivateMethod(method) if !method.function.decorators.is_empty() => {
+          if method.kind != MethodKind::Method {
+            self.check_private_accessor(method.span);
+          }
+          has_private = true;
+          let decs = std::mem::take(&mut method.function.decorators);
+          let decs = self.hoist_decorators(decs, &mut exprs);
+          let kind = method_kind(method.kind);
+          if method.is_static {
+            has_static = true;
+          } else {
+            has_proto = true;
+          }
+
+          // The decorated function is returned by applyDecs, and the private
+          // method is replaced with an accessor calling it. SWC's class properties
+          // transform does not support private getters, so the method calls it instead.
+          let call = self.create_temp(&format!("_call_{}", method.key.id.sym));
+          targets.push(call.clone());
+          let function = if self.lower_class_fields && method.kind == MethodKind::Method {
+            std::mem::replace(&mut method.function, create_private_method_fn(&call))
+          } else {
+            let function = std::mem::replace(
+              &mut method.function,
+              create_private_accessor_fn(&call, method.kind),
+            );
+            if method.kind == MethodKind::Method {
+              method.kind = MethodKind::Getter;
+            }
+            function
+          };
+          member_decs.push(create_array(vec![
+            Expr::Array(decs),
+            create_kind(kind, method.is_static),
+            create_str(&method.key.id.sym),
+            create_fn_expr(function),
+          ]));
+        }
+        ClassMember::ClassProp(prop) if !prop.decorators.is_empty() => {
+          let decs = std::mem::take(&mut prop.decorators);
+          let decs = self.hoist_decorators(decs, &mut exprs);
+          let name = self.hoist_prop_key(prop, &mut exprs);
+          let init = self.create_temp(&format!("_init_{}", temp_name(&name)));
+          targets.push(init.clone());
+          let this = if prop.is_static {
+            static_receiver.clone()
+          } else {
+            Expr::This(ThisExpr { span: DUMMY_SP })
+          };
+          prop.value = Some(Box::new(create_field_init(&init, this, prop.value.take())));
+          member_decs.push(create_array(vec![
+            Expr::Array(decs),
+            create_kind(FIELD, prop.is_static),
+            name,
+          ]));
+        }
+        ClassMember::PrivateProp(prop) if !prop.decorators.is_empty() => {
+          has_private = true;
+          let decs = std::mem::take(&mut prop.decorators);
+          let decs = self.hoist_decorators(decs, &mut exprs);
+          let init = self.create_temp(&format!("_init_{}", prop.key.id.sym));
+          targets.push(init.clone());
+          let this = if prop.is_static {
+            static_receiver.clone()
+          } else {
+            Expr::This(ThisExpr { span: DUMMY_SP })
+          };
+          prop.value = Some(Box::new(create_field_init(&init, this, prop.value.take())));
+          member_decs.push(create_array(vec![
+            Expr::Array(decs),
+            create_kind(FIELD, prop.is_static),
+            create_str(&prop.key.id.sym),
+            create_fn_expr(create_private_getter(&prop.key)),
+            create_fn_expr(create_private_setter(&prop.key)),
+          ]));
+        }
+        _ => {}
+      }
+      class.body.push(member);
+    }
+
+    if has_proto {
+      let init_proto = self.create_temp("_initProto");
+      targets.push(init_proto.clone());
+      inject_init_proto(&mut class, &init_proto);
+    }
+
+    let init_static = if has_static {
+      let init_static = self.create_temp("_initStatic");
+      targets.push(init_static.clone());
+      Some(init_static)
+    } else {
+      None
+    };
+
+    let class_result = if class_decs.elems.is_empty() {
+      None
+    } else {
+      let new_class = self.create_temp("_class");
+      let init_class = self.create_temp("_initClass");
+      targets.push(new_class.clone());
+      targets.push(init_class.clone());
+      Some((new_class, init_class))
+    };
+
+    // SWC's class properties transform only replaces private names in methods, so the
+    // functions accessing private members are returned from a temporary static method:
+    // static [_memberDecs = Symbol()]() { return [...]; }
+    let mut member_decs = create_array(member_decs);
+    let mut cleanup = None;
+    if self.lower_class_fields && has_private {
+      let key = self.create_temp("_memberDecs");
+      let symbol = create_call(Expr::Ident(Ident::new("Symbol".into(), DUMMY_SP)), vec![]);
+      class.body.push(create_method(
+        create_computed_key(create_assign(&key, symbol)),
+        create_function(
+          vec![],
+          vec![Stmt::Return(ReturnStmt {
+            span: DUMMY_SP,
+            arg: Some(Box::new(member_decs)),
+          })],
+        ),
+        MethodKind::Method,
+        true,
+      ));
+      member_decs = create_call(create_computed_member(receiver.clone(), &key), vec![]);
+      cleanup = Some(Expr::Unary(UnaryExpr {
+        span: DUMMY_SP,
+        op: UnaryOp::Delete,
+        arg: Box::new(create_computed_member(receiver.clone(), &key)),
+      }));
+    }
+
+    // static #_ = ([...] = _applyDecs(this, [...], [...]), _initStatic(this));
+    let apply_decs = self
+      .apply_decs
+      .get_or_insert_with(|| private_ident("_applyDecs"))
+      .clone();
+    let mut init = vec![Expr::Assign(AssignExpr {
+      span: DUMMY_SP,
+      op: AssignOp::Assign,
+      left: PatOrExpr::Pat(Box::new(Pat::Array(ArrayPat {
+        span: DUMMY_SP,
+        elems: targets
+          .into_iter()
+          .map(|ident| Some(Pat::Ident(BindingIdent::from(ident))))
+          .collect(),
+        optional: false,
+        type_ann: None,
+      }))),
+      right: Box::new(create_call(
+        Expr::Ident(apply_decs),
+        vec![receiver.clone(), member_decs, Expr::Array(class_decs)],
+      )),
+    })];
+    init.extend(cleanup);
+    if let Some(init_static) = init_static {
+      init.push(create_call(Expr::Ident(init_static), vec![receiver]));
+    }
+    class.body.insert(
+      0,
+      create_private_prop(
+        PrivateName {
+          span: DUMMY_SP,
+          id: Ident::new("_".into(), DUMMY_SP),
+        },
+        Some(create_seq(init)),
+        true,
+      ),
+    );
+
+    DecoratedClass {
+      exprs,
+      class,
+      class_result,
+    }
+  }
+
+  /// Compiles a decorated class expression to `(decorators..., class {}, _initClass(), _Foo)`.
+  fn transform_class_expr(&mut self, ident: Option<Ident>, class: Class) -> Expr {
+    // The class must have a name to reference it from its static initializers.
+    let ident = match ident {
+      None if self.lower_class_fields => Some(private_ident("_class")),
+      ident => ident,
+    };
+    let DecoratedClass {
+      mut exprs,
+      class,
+      class_result,
+    } = self.transform_class(ident.as_ref(), class);
+
+    exprs.push(Expr::Class(ClassExpr { ident, class }));
+    if let Some((new_class, init_class)) = class_result {
+      exprs.push(create_call(Expr::Ident(init_class), vec![]));
+      exprs.push(Expr::Ident(new_class));
+    }
+
+    if exprs.len() == 1 {
+      exprs.pop().unwrap()
+    } else {
+      Expr::Paren(ParenExpr {
+        span: DUMMY_SP,
+        expr: create_seq(exprs),
+      })
+    }
+  }
+
+  /// Compiles a decorated class declaration, keeping it a declaration so its name is
+  /// preserved. Returns the statements to insert before and after it, which evaluate
+  /// the decorators, and replace the class with the result of the class decorators.
+  fn transform_class_decl(&mut self, decl: ClassDecl) -> (Option<Stmt>, ClassDecl, Option<Stmt>) {
+    let DecoratedClass {
+      exprs,
+      class,
+      class_result,
+    } = self.transform_class(Some(&decl.ident), decl.class);
+
+    let before = if exprs.is_empty() {
+      None
+    } else {
+      Some(create_expr_stmt(*create_seq(exprs)))
+    };
+
+    // Foo = _Foo, _initClass();
+    let ident = decl.ident;
+    let after = class_result.map(|(new_class, init_class)| {
+      create_expr_stmt(*create_seq(vec![
+        create_assign(&ident, Expr::Ident(new_class)),
+        create_call(Expr::Ident(init_class), vec![]),
+      ]))
+    });
+
+    (
+      before,
+      ClassDecl {
+        ident,
+        class,
+        declare: decl.declare,
+      },
+      after,
+    )
+  }
+
+  /// Returns an array of the decorators, assigning those that may have side effects
+  /// to temporary variables so they are evaluated before the class.
+  fn hoist_decorators(&mut self, decorators: Vec<Decorator>, exprs: &mut Vec<Expr>) -> ArrayLit {
+    let elems = decorators
+      .into_iter()
+      .map(|decorator| {
+        let expr = match *decorator.expr {
+          Expr::Ident(ident) => Expr::Ident(ident),
+          expr => {
+            let temp = self.create_temp("_dec");
+            exprs.push(create_assign(&temp, expr));
+            Expr::Ident(temp)
+          }
+        };
+        Some(ExprOrSpread {
+          spread: None,
+          expr: Box::new(expr),
+        })
+      })
+      .collect();
+
+    ArrayLit {
+      span: DUMMY_SP,
+      elems,
+    }
+  }
+
+  /// Returns the name of a member, moving computed keys to a temporary variable.
+  fn hoist_key(&mut self, key: &mut PropName, exprs: &mut Vec<Expr>) -> Expr {
+    match key {
+      PropName::Ident(ident) => create_str(&ident.sym),
+      PropName::Str(s) => Expr::Lit(Lit::Str(s.clone())),
+      PropName::Num(n) => Expr::Lit(Lit::Num(*n)),
+      PropName::BigInt(b) => create_str(&b.value.to_string()),
+      PropName::Computed(computed) => {
+        let temp = self.create_temp("_computedKey");
+        let expr = std::mem::replace(&mut *computed.expr, Expr::Ident(temp.clone()));
+        exprs.push(create_assign(&temp, expr));
+        Expr::Ident(temp)
+      }
+    }
+  }
+
+  fn hoist_prop_key(&mut self, prop: &mut ClassProp, exprs: &mut Vec<Expr>) -> Expr {
+    if prop.computed {
+      let temp = self.create_temp("_computedKey");
+      let expr = std::mem::replace(&mut *prop.key, Expr::Ident(temp.clone()));
+      exprs.push(create_assign(&temp, expr));
+      return Expr::Ident(temp);
+    }
+
+    match &*prop.key {
+      Expr::Ident(ident) => create_str(&ident.sym),
+      key => key.clone(),
+    }
+  }
+
+  /// Returns whether the member was declared with the `accessor` keyword.
+  fn is_accessor(&self, member: &ClassMember) -> bool {
+    let (span, key) = match member {
+      ClassMember::ClassProp(prop) => (prop.span, prop.key.span()),
+      ClassMember::PrivateProp(prop) => (prop.span, prop.key.span),
+      _ => return false,
+    };
+    self
+      .accessors
+      .iter()
+      .any(|pos| span.lo <= *pos && *pos <= key.lo)
+  }
+
+  /// Compiles an undecorated accessor. A private accessor cannot be observed
+  /// differently from a private field, so it becomes one.
+  fn lower_accessor(
+    &mut self,
+    accessor: Accessor,
+    names: &mut HashSet<JsWord>,
+  ) -> Vec<ClassMember> {
+    match accessor.key {
+      AccessorKey::Public(key) => {
+        let storage = storage_name(names, &prop_temp_name(&key));
+        // A computed key is evaluated once, when the getter is defined.
+        let (get_key, set_key) = match key {
+          PropName::Computed(computed) => {
+            let temp = self.create_temp("_computedKey");
+            (
+              create_computed_key(create_assign(&temp, *computed.expr)),
+              create_computed_key(Expr::Ident(temp)),
+            )
+          }
+          key => (key.clone(), key),
+        };
+        create_accessor_members(
+          get_key,
+          set_key,
+          storage,
+          accessor.value,
+          accessor.is_static,
+        )
+      }
+      AccessorKey::Private(key) => {
+        vec![create_private_prop(key, accessor.value, accessor.is_static)]
+      }
+    }
+  }
+
+  fn check_private_accessor(&mut self, span: Span) {
+    if !self.lower_class_fields {
+      return;
+    }
+
+    self.diagnostics.push(Diagnostic {
+      message: "Decorators on private getters, setters and accessors are not supported when class fields are compiled for the targets.".into(),
+      code_highlights: Some(vec![CodeHighlight {
+        message: None,
+        loc: SourceLocation::from(self.source_map, span),
+      }]),
+      hints: Some(vec![
+        "Make the member public, or use targets that support class fields.".into(),
+      ]),
+      show_environment: true,
+      severity: DiagnosticSeverity::Error,
+      documentation_url: None,
+    });
+  }
+
+  fn create_temp(&mut self, name: &str) -> Ident {
+    let ident = private_ident(name);
+    self.vars.push(ident.clone());
+    ident
+  }
+}
+
+/// Converts a field declared with the `accessor` keyword.
+fn into_accessor(member: ClassMember) -> Accessor {
+  match member {
+    ClassMember::ClassProp(prop) => {
+      let key = match *prop.key {
+        Expr::Ident(ident) if !prop.computed => PropName::Ident(ident),
+        Expr::Lit(Lit::Str(s)) if !prop.computed => PropName::Str(s),
+        Expr::Lit(Lit::Num(n)) if !prop.computed => PropName::Num(n),
+        Expr::Lit(Lit::BigInt(b)) if !prop.computed => PropName::BigInt(b),
+        key => PropName::Computed(ComputedPropName {
+          span: key.span(),
+          expr: Box::new(key),
+        }),
+      };
+      Accessor {
+        span: prop.span,
+        key: AccessorKey::Public(key),
+        value: prop.value,
+        is_static: prop.is_static,
+        decorators: prop.decorators,
+      }
+    }
+    ClassMember::PrivateProp(prop) => Accessor {
+      span: prop.span,
+      key: AccessorKey::Private(prop.key),
+      value: prop.value,
+      is_static: prop.is_static,
+      decorators: prop.decorators,
+    },
+    _ => unreachable!("only fields can be accessors"),
+  }
+}
+
+fn has_decorators(class: &Class) -> bool {
+  !class.decorators.is_empty()
+    || class.body.iter().any(|member| match member {
+      ClassMember::Method(method) => !method.function.decorators.is_empty(),
+      ClassMember::PrivateMethod(method) => !method.function.decorators.is_empty(),
+      ClassMember::ClassProp(prop) => !prop.decorators.is_empty(),
+      ClassMember::PrivateProp(prop) => !prop.decorators.is_empty(),
+      _ => false,
+    })
+}
+
+fn method_kind(kind: MethodKind) -> f64 {
+  match kind {
+    MethodKind::Method => METHOD,
+    MethodKind::Getter => GETTER,
+    MethodKind::Setter => SETTER,
+  }
+}
+
+fn create_kind(kind: f64, is_static: bool) -> Expr {
+  Expr::Lit(Lit::Num(Number {
+    span: DUMMY_SP,
+    value: if is_static { kind + STATIC } else { kind },
+  }))
+}
+
+/// A name for the temporary variables of a member, e.g. `_init_x` for `x`.
+fn temp_name(name: &Expr) -> JsWord {
+  match name {
+    Expr::Lit(Lit::Str(s))
+      if s
+        .value
+        .chars()
+        .all(|c| c.is_alphanumeric() || c == '_' || c == '$') =>
+    {
+      s.value.clone()
+    }
+    _ => "computedKey".into(),
+  }
+}
+
+/// Runs the initializers of instance methods before the first field is initialized,
+/// or at the start of the constructor if there are no instance fields.
+fn inject_init_proto(class: &mut Class, init_proto: &Ident) {
+  let call = create_call(
+    Expr::Ident(init_proto.clone()),
+    vec![Expr::This(ThisExpr { span: DUMMY_SP })],
+  );
+
+  for member in &mut class.body {
+    let value = match member {
+      ClassMember::ClassProp(prop) if !prop.is_static && !prop.declare => &mut prop.value,
+      ClassMember::PrivateProp(prop) if !prop.is_static => &mut prop.value,
+      _ => continue,
+    };
+    let init = value.take().unwrap_or_else(|| Box::new(create_void_0()));
+    *value = Some(create_seq(vec![call, *init]));
+    return;
+  }
+
+  for member in &mut class.body {
+    if let ClassMember::Constructor(constructor) = member {
+      inject_after_super(constructor, vec![Box::new(call)]);
+      return;
+    }
+  }
+
+  // constructor(...args) { super(...args); _initProto(this); }
+  let has_super = class.super_class.is_some();
+  let args = private_ident("args");
+  let mut stmts = vec![];
+  if has_super {
+    stmts.push(create_expr_stmt(Expr::Call(CallExpr {
+      span: DUMMY_SP,
+      callee: ExprOrSuper::Super(Super { span: DUMMY_SP }),
+      args: vec![ExprOrSpread {
+        spread: Some(DUMMY_SP),
+        expr: Box::new(Expr::Ident(args.clone())),
+      }],
+      type_args: None,
+    })));
+  }
+  stmts.push(create_expr_stmt(call));
+
+  let params = if has_super {
+    vec![ParamOrTsParamProp::Param(Param {
+      span: DUMMY_SP,
+      decorators: vec![],
+      pat: Pat::Rest(RestPat {
+        span: DUMMY_SP,
+        dot3_token: DUMMY_SP,
+        arg: Box::new(Pat::Ident(BindingIdent::from(args))),
+        type_ann: None,
+      }),
+    })]
+  } else {
+    vec![]
+  };
+
+  class.body.push(ClassMember::Constructor(Constructor {
+    span: DUMMY_SP,
+    key: PropName::Ident(Ident::new("constructor".into(), DUMMY_SP)),
+    params,
+    body: Some(BlockStmt {
+      span: DUMMY_SP,
+      stmts,
+    }),
+    accessibility: None,
+    is_optional: false,
+  }));
+}
+
+/// `_init_x(this, value)`
+fn create_field_init(init: &Ident, this: Expr, value: Option<Box<Expr>>) -> Expr {
+  create_call(
+    Expr::Ident(init.clone()),
+    vec![
+      this,
+      value.map(|value| *value).unwrap_or_else(create_void_0),
+    ],
+  )
+}
+
+/// The body of a decorated private method, which is replaced by an accessor:
+/// `get #m() { return _call_m; }`, `get #g() { return _call_g.call(this); }`
+/// or `set #s(v) { _call_s.call(this, v); }`.
+fn create_private_accessor_fn(call: &Ident, kind: MethodKind) -> Function {
+  let this = Expr::This(ThisExpr { span: DUMMY_SP });
+  let value = private_ident("v");
+  let (params, stmt) = match kind {
+    MethodKind::Method => (
+      vec![],
+      Stmt::Return(ReturnStmt {
+        span: DUMMY_SP,
+        arg: Some(Box::new(Expr::Ident(call.clone()))),
+      }),
+    ),
+    MethodKind::Getter => (
+      vec![],
+      Stmt::Return(ReturnStmt {
+        span: DUMMY_SP,
+        arg: Some(Box::new(create_call(
+          create_member(call, "call"),
+          vec![this],
+        ))),
+      }),
+    ),
+    MethodKind::Setter => (
+      vec![Param {
+        span: DUMMY_SP,
+        decorators: vec![],
+        pat: Pat::Ident(BindingIdent::from(value.clone())),
+      }],
+      create_expr_stmt(create_call(
+        create_member(call, "call"),
+        vec![this, Expr::Ident(value)],
+      )),
+    ),
+  };
+
+  create_function(params, vec![stmt])
+}
+
+/// The body of a decorated private method when class fields are compiled for the targets:
+/// `#m(...args) { return _call_m.apply(this, args); }`
+fn create_private_method_fn(call: &Ident) -> Function {
+  let args = private_ident("args");
+  create_function(
+    vec![Param {
+      span: DUMMY_SP,
+      decorators: vec![],
+      pat: Pat::Rest(RestPat {
+        span: DUMMY_SP,
+        dot3_token: DUMMY_SP,
+        arg: Box::new(Pat::Ident(BindingIdent::from(args.clone()))),
+        type_ann: None,
+      }),
+    }],
+    vec![Stmt::Return(ReturnStmt {
+      span: DUMMY_SP,
+      arg: Some(Box::new(create_call(
+        create_member(call, "apply"),
+        vec![Expr::This(ThisExpr { span: DUMMY_SP }), Expr::Ident(args)],
+      ))),
+    })],
+  )
+}
+
+/// `function () { return this.#x; }`
+fn create_private_getter(key: &PrivateName) -> Function {
+  create_function(
+    vec![],
+    vec![Stmt::Return(ReturnStmt {
+      span: DUMMY_SP,
+      arg: Some(Box::new(create_private_member(key))),
+    })],
+  )
+}
+
+/// `function (v) { this.#x = v; }`
+fn create_private_setter(key: &PrivateName) -> Function {
+  let value = private_ident("v");
+  create_function(
+    vec![Param {
+      span: DUMMY_SP,
+      decorators: vec![],
+      pat: Pat::Ident(BindingIdent::from(value.clone())),
+    }],
+    vec![create_expr_stmt(Expr::Assign(AssignExpr {
+      span: DUMMY_SP,
+      op: AssignOp::Assign,
+      left: PatOrExpr::Expr(Box::new(create_private_member(key))),
+      right: Box::new(Expr::Ident(value)),
+    }))],
+  )
+}
+
+/// `get x() { return this.#_x; } set x(v) { this.#_x = v; } #_x = value;`
+fn create_accessor_members(
+  get_key: PropName,
+  set_key: PropName,
+  storage: PrivateName,
+  value: Option<Box<Expr>>,
+  is_static: bool,
+) -> Vec<ClassMember> {
+  vec![
+    create_method(
+      get_key,
+      create_private_getter(&storage),
+      MethodKind::Getter,
+      is_static,
+    ),
+    create_method(
+      set_key,
+      create_private_setter(&storage),
+      MethodKind::Setter,
+      is_static,
+    ),
+    create_private_prop(storage, value, is_static),
+  ]
+}
+
+/// Returns a private name for the storage of an accessor, e.g. `#_x`, that is not used in the class.
+fn storage_name(names: &mut HashSet<JsWord>, name: &str) -> PrivateName {
+  let mut sym: JsWord = format!("_{}", name).into();
+  let mut i = 1;
+  while names.contains(&sym) {
+    sym = format!("_{}{}", name, i).into();
+    i += 1;
+  }
+  names.insert(sym.clone());
+  PrivateName {
+    span: DUMMY_SP,
+    id: Ident::new(sym, DUMMY_SP),
+  }
+}
+
+fn prop_temp_name(key: &PropName) -> JsWord {
+  match key {
+    PropName::Ident(ident) => ident.sym.clone(),
+    PropName::Str(s) => temp_name(&Expr::Lit(Lit::Str(s.clone()))),
+    _ => "computedKey".into(),
+  }
+}
+
+/// Returns the private names declared or referenced in a class, including nested classes.
+fn private_names(class: &Class) -> HashSet<JsWord> {
+  let mut collector = PrivateNameCollector(HashSet::new());
+  class.visit_with(&Invalid { span: DUMMY_SP } as _, &mut collector);
+  collector.0
+}
+
+struct PrivateNameCollector(HashSet<JsWord>);
+
+impl Visit for PrivateNameCollector {
+  fn visit_private_name(&mut self, node: &PrivateName, _parent: &dyn Node) {
+    self.0.insert(node.id.sym.clone());
+  }
+}
+
+fn create_private_prop(key: PrivateName, value: Option<Box<Expr>>, is_static: bool) -> ClassMember {
+  ClassMember::PrivateProp(PrivateProp {
+    span: DUMMY_SP,
+    key,
+    value,
+    type_ann: None,
+    is_static,
+    decorators: vec![],
+    computed: false,
+    accessibility: None,
+    is_abstract: false,
+    is_optional: false,
+    is_override: false,
+    readonly: false,
+    definite: false,
+  })
+}
+
+fn create_method(
+  key: PropName,
+  function: Function,
+  kind: MethodKind,
+  is_static: bool,
+) -> ClassMember {
+  ClassMember::Method(ClassMethod {
+    span: DUMMY_SP,
+    key,
+    function,
+    kind,
+    is_static,
+    accessibility: None,
+    is_abstract: false,
+    is_optional: false,
+    is_override: false,
+  })
+}
+
+fn create_private_method(
+  key: PrivateName,
+  function: Function,
+  kind: MethodKind,
+  is_static: bool,
+) -> ClassMember {
+  ClassMember::PrivateMethod(PrivateMethod {
+    span: DUMMY_SP,
+    key,
+    function,
+    kind,
+    is_static,
+    accessibility: None,
+    is_abstract: false,
+    is_optional: false,
+    is_override: false,
+  })
+}
+
+fn create_fn_expr(function: Function) -> Expr {
+  Expr::Fn(FnExpr {
+    ident: None,
+    function,
+  })
+}
+
+fn create_private_member(key: &PrivateName) -> Expr {
+  Expr::Member(MemberExpr {
+    span: DUMMY_SP,
+    obj: ExprOrSuper::Expr(Box::new(Expr::This(ThisExpr { span: DUMMY_SP }))),
+    prop: Box::new(Expr::PrivateName(key.clone())),
+    computed: false,
+  })
+}
+
+fn create_function(params: Vec<Param>, stmts: Vec<Stmt>) -> Function {
+  Function {
+    params,
+    decorators: vec![],
+    span: DUMMY_SP,
+    body: Some(BlockStmt {
+      span: DUMMY_SP,
+      stmts,
+    }),
+    is_generator: false,
+    is_async: false,
+    type_params: None,
+    return_type: None,
+  }
+}
+
+fn create_let_decl(ident: Ident, init: Expr) -> Stmt {
+  Stmt::Decl(Decl::Var(VarDecl {
+    span: DUMMY_SP,
+    kind: VarDeclKind::Let,
+    declare: false,
+    decls: vec![VarDeclarator {
+      span: DUMMY_SP,
+      name: Pat::Ident(BindingIdent::from(ident)),
+      init: Some(Box::new(init)),
+      definite: false,
+    }],
+  }))
+}
+
+fn create_var_decl(vars: Vec<Ident>) -> Stmt {
+  Stmt::Decl(Decl::Var(VarDecl {
+    span: DUMMY_SP,
+    kind: VarDeclKind::Var,
+    declare: false,
+    decls: vars
+      .into_iter()
+      .map(|ident| VarDeclarator {
+        span: DUMMY_SP,
+        name: Pat::Ident(BindingIdent::from(ident)),
+        init: None,
+        definite: false,
+      })
+      .collect(),
+  }))
+}
+
+fn create_assign(ident: &Ident, expr: Expr) -> Expr {
+  Expr::Assign(AssignExpr {
+    span: DUMMY_SP,
+    op: AssignOp::Assign,
+    left: PatOrExpr::Pat(Box::new(Pat::Ident(BindingIdent::from(ident.clone())))),
+    right: Box::new(expr),
+  })
+}
+
+fn create_call(callee: Expr, args: Vec<Expr>) -> Expr {
+  Expr::Call(CallExpr {
+    span: DUMMY_SP,
+    callee: ExprOrSuper::Expr(Box::new(callee)),
+    args: args
+      .into_iter()
+      .map(|expr| ExprOrSpread {
+        spread: None,
+        expr: Box::new(expr),
+      })
+      .collect(),
+    type_args: None,
+  })
+}
+
+fn create_member(obj: &Ident, prop: &str) -> Expr {
+  Expr::Member(MemberExpr {
+    span: DUMMY_SP,
+    obj: ExprOrSuper::Expr(Box::new(Expr::Ident(obj.clone()))),
+    prop: Box::new(Expr::Ident(Ident::new(prop.into(), DUMMY_SP))),
+    computed: false,
+  })
+}
+
+fn create_computed_member(obj: Expr, prop: &Ident) -> Expr {
+  Expr::Member(MemberExpr {
+    span: DUMMY_SP,
+    obj: ExprOrSuper::Expr(Box::new(obj)),
+    prop: Box::new(Expr::Ident(prop.clone())),
+    computed: true,
+  })
+}
+
+fn create_computed_key(expr: Expr) -> PropName {
+  PropName::Computed(ComputedPropName {
+    span: DUMMY_SP,
+    expr: Box::new(expr),
+  })
+}
+
+fn create_array(elems: Vec<Expr>) -> Expr {
+  Expr::Array(ArrayLit {
+    span: DUMMY_SP,
+    elems: elems
+      .into_iter()
+      .map(|expr| {
+        Some(ExprOrSpread {
+          spread: None,
+          expr: Box::new(expr),
+        })
+      })
+      .collect(),
+  })
+}
+
+fn create_seq(exprs: Vec<Expr>) -> Box<Expr> {
+  Box::new(Expr::Seq(SeqExpr {
+    span: DUMMY_SP,
+    exprs: exprs.into_iter().map(Box::new).collect(),
+  }))
+}
+
+fn create_str(value: &str) -> Expr {
+  Expr::Lit(Lit::Str(Str {
+    span: DUMMY_SP,
+    value: value.into(),
+    has_escape: false,
+    kind: StrKind::Synthesized,
+  }))
+}
+
+fn create_void_0() -> Expr {
+  Expr::Unary(UnaryExpr {
+    span: DUMMY_SP,
+    op: UnaryOp::Void,
+    arg: Box::new(Expr::Lit(Lit::Num(Number {
+      span: DUMMY_SP,
+      value: 0.0,
+    }))),
+  })
+}
+
+fn create_expr_stmt(expr: Expr) -> Stmt {
+  Stmt::Expr(ExprStmt {
+    span: DUMMY_SP,
+    expr: Box::new(expr),
+  })
+}
+
+/// An identifier that is renamed by the hygiene pass if it conflicts with another binding.
+fn private_ident(name: &str) -> Ident {
+  Ident::new(name.into(), DUMMY_SP.apply_mark(Mark::fresh(Mark::root())))
+}
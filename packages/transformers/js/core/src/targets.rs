@@ -0,0 +1,228 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use swc_ecma_preset_env::{Version, Versions};
+
+use crate::utils::{Diagnostic, DiagnosticSeverity};
+
+/// Engines to compile for. Either a map of engine names to minimum versions,
+/// or one or more browserslist queries, e.g. `"> 0.5%, not dead"`.
+#[derive(Serialize, Debug, Deserialize)]
+#[serde(untagged)]
+pub enum Targets {
+  Versions(HashMap<String, String>),
+  Query(String),
+  Queries(Vec<String>),
+}
+
+/// Converts targets to the minimum version of each engine. Browserslist queries are resolved
+/// natively. Unparsable queries or versions, and unknown engines in a map of versions produce
+/// warnings.
+pub fn targets_to_versions(targets: &Targets, diagnostics: &mut Vec<Diagnostic>) -> Versions {
+  let mut versions = Versions::default();
+  match targets {
+    Targets::Versions(targets) => {
+      for (engine, version) in targets {
+        let parsed = match parse_version(version) {
+          Some(version) => version,
+          None => {
+            diagnostics.push(warning(format!(
+              "Invalid version \"{}\" for target engine \"{}\".",
+              version, engine
+            )));
+            continue;
+          }
+        };
+
+        if !set_min_version(&mut versions, engine, parsed) {
+          diagnostics.push(warning(format!("Unknown target engine \"{}\".", engine)));
+        }
+      }
+    }
+    Targets::Query(query) => resolve_queries(&[query.as_str()], &mut versions, diagnostics),
+    Targets::Queries(queries) => resolve_queries(queries, &mut versions, diagnostics),
+  }
+
+  versions
+}
+
+fn resolve_queries<S: AsRef<str>>(
+  queries: &[S],
+  versions: &mut Versions,
+  diagnostics: &mut Vec<Diagnostic>,
+) {
+  let opts = browserslist::Opts {
+    mobile_to_desktop: true,
+    ..Default::default()
+  };
+
+  let distribs = match browserslist::resolve(queries, &opts) {
+    Ok(distribs) => distribs,
+    Err(err) => {
+      let queries: Vec<&str> = queries.iter().map(|q| q.as_ref()).collect();
+      diagnostics.push(warning(format!(
+        "Could not resolve browserslist query \"{}\": {}",
+        queries.join(", "),
+        err
+      )));
+      return;
+    }
+  };
+
+  for distrib in &distribs {
+    let engine = match distrib.name() {
+      "and_chr" => "chrome",
+      "and_ff" => "firefox",
+      "ie_mob" => "ie",
+      "ios_saf" => "ios",
+      "op_mob" => "opera",
+      name => name,
+    };
+
+    // Ranges such as "15.2-15.3" use their lower bound. Browsers without a numeric
+    // version (e.g. "op_mini all", "safari TP") are skipped. So are browsers that preset_env
+    // has no data for (e.g. kaios, and_uc), which most queries such as "defaults" include.
+    let version = distrib.version().split('-').next().unwrap_or_default();
+    if let Some(version) = parse_version(version) {
+      set_min_version(versions, engine, version);
+    }
+  }
+}
+
+/// Sets the version of an engine, unless it already has a lower one.
+/// Returns false if the engine is unknown.
+fn set_min_version(versions: &mut Versions, engine: &str, version: Version) -> bool {
+  let mut found = false;
+  *versions = versions.map(|name, current| {
+    if name != engine {
+      return current;
+    }
+
+    found = true;
+    match current {
+      Some(current) if current <= version => Some(current),
+      _ => Some(version),
+    }
+  });
+
+  found
+}
+
+fn parse_version(version: &str) -> Option<Version> {
+  let mut parts = version.trim().split('.');
+  let major = parts.next()?.parse().ok()?;
+  let minor = match parts.next() {
+    Some(minor) => minor.parse().ok()?,
+    None => 0,
+  };
+  let patch = match parts.next() {
+    Some(patch) => patch.parse().ok()?,
+    None => 0,
+  };
+  if parts.next().is_some() {
+    return None;
+  }
+
+  Some(Version {
+    major,
+    minor,
+    patch,
+  })
+}
+
+fn warning(message: String) -> Diagnostic {
+  Diagnostic {
+    message,
+    code_highlights: None,
+    hints: None,
+    show_environment: false,
+    severity: DiagnosticSeverity::Warning,
+    documentation_url: None,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn resolve(targets: Targets) -> (Versions, Vec<String>) {
+    let mut diagnostics = Vec::new();
+    let versions = targets_to_versions(&targets, &mut diagnostics);
+    (
+      versions,
+      diagnostics.into_iter().map(|d| d.message).collect(),
+    )
+  }
+
+  fn version(major: u16, minor: u16) -> Option<Version> {
+    Some(Version {
+      major,
+      minor,
+      patch: 0,
+    })
+  }
+
+  #[test]
+  fn versions() {
+    let mut targets = HashMap::new();
+    targets.insert("chrome".into(), "80".into());
+    targets.insert("node".into(), "12.4".into());
+    let (versions, warnings) = resolve(Targets::Versions(targets));
+    assert_eq!(versions.chrome, version(80, 0));
+    assert_eq!(versions.node, version(12, 4));
+    assert!(warnings.is_empty());
+  }
+
+  #[test]
+  fn version_warnings() {
+    let mut targets = HashMap::new();
+    targets.insert("foo".into(), "1".into());
+    let (_, warnings) = resolve(Targets::Versions(targets));
+    assert_eq!(warnings, vec!["Unknown target engine \"foo\"."]);
+
+    let mut targets = HashMap::new();
+    targets.insert("chrome".into(), "latest".into());
+    let (versions, warnings) = resolve(Targets::Versions(targets));
+    assert_eq!(versions.chrome, None);
+    assert_eq!(
+      warnings,
+      vec!["Invalid version \"latest\" for target engine \"chrome\"."]
+    );
+  }
+
+  #[test]
+  fn queries() {
+    let (versions, warnings) = resolve(Targets::Queries(vec![
+      "chrome 80".into(),
+      "chrome 90".into(),
+      "and_ff 95".into(),
+      "safari 15.2-15.3".into(),
+    ]));
+    assert_eq!(versions.chrome, version(80, 0));
+    assert_eq!(versions.firefox, version(95, 0));
+    assert_eq!(versions.safari, version(15, 2));
+    assert!(warnings.is_empty());
+
+    let (versions, warnings) = resolve(Targets::Query("ie 11, op_mini all".into()));
+    assert_eq!(versions.ie, version(11, 0));
+    assert!(warnings.is_empty());
+  }
+
+  #[test]
+  fn query_warnings() {
+    let (versions, warnings) = resolve(Targets::Query("chrome 80, kaios 2.5, and_uc 15.5".into()));
+    assert_eq!(versions.chrome, version(80, 0));
+    assert!(warnings.is_empty());
+
+    let (versions, warnings) = resolve(Targets::Queries(vec![
+      "defaults".into(),
+      "last 2 versions".into(),
+    ]));
+    assert!(versions.chrome.is_some());
+    assert!(warnings.is_empty());
+
+    let (_, warnings) = resolve(Targets::Query("not a query".into()));
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].starts_with("Could not resolve browserslist query \"not a query\": "));
+  }
+}
@@ -3,7 +3,7 @@ use std::collections::HashSet;
 
 use serde::{Deserialize, Serialize};
 use swc_atoms::JsWord;
-use swc_common::{Mark, Span, SyntaxContext, DUMMY_SP};
+use swc_common::{Mark, Span, Spanned, SyntaxContext, DUMMY_SP};
 use swc_ecmascript::ast;
 
 pub fn match_member_expr(
@@ -133,6 +133,36 @@ pub fn match_require(
   }
 }
 
+/// Returns the directives in the prologue of a module, e.g. `"use strict"` or `"use client"`.
+/// Statements inserted by earlier passes, such as the JSX runtime import, are skipped.
+pub fn get_directives(module: &ast::Module) -> Vec<JsWord> {
+  use ast::*;
+
+  let mut directives = vec![];
+  for item in &module.body {
+    if item.span().is_dummy() {
+      continue;
+    }
+
+    match item {
+      ModuleItem::Stmt(Stmt::Expr(ExprStmt { expr, .. })) => match &**expr {
+        Expr::Lit(Lit::Str(str_)) => directives.push(str_.value.clone()),
+        _ => break,
+      },
+      _ => break,
+    }
+  }
+  directives
+}
+
+/// Returns whether the module has import or export statements.
+pub fn has_module_decls(module: &ast::Module) -> bool {
+  module
+    .body
+    .iter()
+    .any(|item| matches!(item, ast::ModuleItem::ModuleDecl(_)))
+}
+
 pub fn match_import(node: &ast::Expr, ignore_mark: Mark) -> Option<JsWord> {
   use ast::*;
 
@@ -160,10 +190,10 @@ pub fn match_import(node: &ast::Expr, ignore_mark: Mark) -> Option<JsWord> {
 
 #[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
 pub struct SourceLocation {
-  start_line: usize,
-  start_col: usize,
-  end_line: usize,
-  end_col: usize,
+  pub start_line: usize,
+  pub start_col: usize,
+  pub end_line: usize,
+  pub end_col: usize,
 }
 
 impl SourceLocation {
@@ -226,6 +256,26 @@ pub enum SourceType {
   Module,
 }
 
+impl Default for SourceType {
+  fn default() -> Self {
+    SourceType::Module
+  }
+}
+
+#[derive(Serialize, Debug, Deserialize, Eq, PartialEq, Clone, Copy)]
+pub enum DecoratorsVersion {
+  /// TypeScript's `experimentalDecorators`, also supported by Babel's legacy mode.
+  Legacy,
+  /// The standardized decorators proposal, as of March 2022.
+  Standard,
+}
+
+impl Default for DecoratorsVersion {
+  fn default() -> Self {
+    DecoratorsVersion::Legacy
+  }
+}
+
 pub struct Bailout {
   pub loc: SourceLocation,
   pub reason: BailoutReason,
@@ -2,8 +2,11 @@ extern crate napi;
 #[macro_use]
 extern crate napi_derive;
 extern crate parcel_js_swc_core;
+extern crate rayon;
 
-use napi::{CallContext, JsObject, JsUnknown, Result};
+use napi::{CallContext, Env, Error, JsNumber, JsObject, JsUnknown, Result, Task, ValueType};
+use rayon::prelude::*;
+use std::convert::TryInto;
 
 #[cfg(target_os = "macos")]
 #[global_allocator]
@@ -22,9 +25,99 @@ fn transform(ctx: CallContext) -> Result<JsUnknown> {
   ctx.env.to_js_value(&result)
 }
 
+struct TransformTask {
+  config: Option<parcel_js_swc_core::Config>,
+}
+
+impl Task for TransformTask {
+  type Output = parcel_js_swc_core::TransformResult;
+  type JsValue = JsUnknown;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    let config = self.config.take().unwrap();
+    Ok(parcel_js_swc_core::transform(config)?)
+  }
+
+  fn resolve(self, env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    env.to_js_value(&output)
+  }
+}
+
+#[js_function(1)]
+fn transform_async(ctx: CallContext) -> Result<JsObject> {
+  // The config is deserialized on the main thread since it reads from JS values.
+  // The transform itself runs on the libuv thread pool.
+  let opts = ctx.get::<JsObject>(0)?;
+  let config: parcel_js_swc_core::Config = ctx.env.from_js_value(opts)?;
+
+  let task = TransformTask {
+    config: Some(config),
+  };
+  ctx.env.spawn(task).map(|work| work.promise_object())
+}
+
+struct TransformManyTask {
+  configs: Vec<std::result::Result<parcel_js_swc_core::Config, String>>,
+}
+
+impl Task for TransformManyTask {
+  type Output = Vec<std::result::Result<parcel_js_swc_core::TransformResult, String>>;
+  type JsValue = JsObject;
+
+  fn compute(&mut self) -> Result<Self::Output> {
+    let configs = std::mem::take(&mut self.configs);
+    Ok(
+      configs
+        .into_par_iter()
+        .map(|config| {
+          config.and_then(|config| parcel_js_swc_core::transform(config).map_err(|e| e.to_string()))
+        })
+        .collect(),
+    )
+  }
+
+  fn resolve(self, env: Env, output: Self::Output) -> Result<Self::JsValue> {
+    // Failed items are returned as Error objects in place of their result,
+    // so that one bad file doesn't reject the whole batch.
+    let mut results = env.create_array_with_length(output.len())?;
+    for (i, result) in output.into_iter().enumerate() {
+      let value = match result {
+        Ok(result) => env.to_js_value(&result)?,
+        Err(reason) => env.create_error(Error::from_reason(reason))?.into_unknown(),
+      };
+      results.set_element(i as u32, value)?;
+    }
+    Ok(results)
+  }
+}
+
+#[js_function(1)]
+fn transform_many(ctx: CallContext) -> Result<JsObject> {
+  let opts = ctx.get::<JsObject>(0)?;
+  let length: u32 = opts.get_named_property::<JsNumber>("length")?.try_into()?;
+  let mut configs = Vec::with_capacity(length as usize);
+  for i in 0..length {
+    // Invalid items are reported in place of their result, like transform errors.
+    let config = opts.get_element::<JsUnknown>(i)?;
+    let config = match config.get_type()? {
+      ValueType::Object => ctx
+        .env
+        .from_js_value::<parcel_js_swc_core::Config, _>(config)
+        .map_err(|e| e.reason),
+      _ => Err(format!("Expected an object at index {}", i)),
+    };
+    configs.push(config);
+  }
+
+  let task = TransformManyTask { configs };
+  ctx.env.spawn(task).map(|work| work.promise_object())
+}
+
 #[module_exports]
 fn init(mut exports: JsObject) -> Result<()> {
   exports.create_named_method("transform", transform)?;
+  exports.create_named_method("transformAsync", transform_async)?;
+  exports.create_named_method("transformMany", transform_many)?;
 
   Ok(())
 }
@@ -11,4 +11,24 @@ function transformWrapper(config) {
   };
 }
 
-export {transformWrapper as transform};
+function transformAsyncWrapper(config) {
+  return Promise.resolve().then(() => transformWrapper(config));
+}
+
+function transformManyWrapper(configs) {
+  return Promise.resolve().then(() =>
+    configs.map(config => {
+      try {
+        return transformWrapper(config);
+      } catch (err) {
+        return err;
+      }
+    }),
+  );
+}
+
+export {
+  transformWrapper as transform,
+  transformAsyncWrapper as transformAsync,
+  transformManyWrapper as transformMany,
+};
@@ -26,6 +26,22 @@ if (process.env.PARCEL_BUILD_ENV === 'production') {
       code: Buffer.from(result.code.buffer),
     };
   };
+
+  module.exports.transformAsync = function(config) {
+    return Promise.resolve().then(() => module.exports.transform(config));
+  };
+
+  module.exports.transformMany = function(configs) {
+    return Promise.resolve().then(() =>
+      configs.map(config => {
+        try {
+          return module.exports.transform(config);
+        } catch (err) {
+          return err;
+        }
+      }),
+    );
+  };
 } else if (require('fs').existsSync(require('path').join(__dirname, name))) {
   module.exports = require(`./${name}`);
 } else {
@@ -4,9 +4,8 @@ import type {SchemaEntity} from '@parcel/utils';
 import type {Diagnostic} from '@parcel/diagnostic';
 import SourceMap from '@parcel/source-map';
 import {Transformer} from '@parcel/plugin';
-import {init, transform} from '../native';
+import {init, transformAsync} from '../native';
 import path from 'path';
-import browserslist from 'browserslist';
 import semver from 'semver';
 import nullthrows from 'nullthrows';
 import ThrowableDiagnostic, {encodeJSONKeyComponent} from '@parcel/diagnostic';
@@ -41,20 +40,6 @@ const JSX_PRAGMA = {
   },
 };
 
-const BROWSER_MAPPING = {
-  and_chr: 'chrome',
-  and_ff: 'firefox',
-  ie_mob: 'ie',
-  ios_saf: 'ios',
-  op_mob: 'opera',
-  and_qq: null,
-  and_uc: null,
-  baidu: null,
-  bb: null,
-  kaios: null,
-  op_mini: null,
-};
-
 // List of browsers to exclude when the esmodule target is specified.
 // Based on https://caniuse.com/#feat=es6-module
 const ESMODULE_BROWSERS = [
@@ -79,6 +64,15 @@ const ESMODULE_BROWSERS = [
   'not kaios > 0',
 ];
 
+// Returns the lowest version matched by a semver range, or null if there is none.
+function getMinVersion(range: string): ?string {
+  try {
+    return semver.minVersion(range)?.toString() ?? null;
+  } catch (err) {
+    return null;
+  }
+}
+
 const CONFIG_SCHEMA: SchemaEntity = {
   type: 'object',
   properties: {
@@ -98,6 +92,13 @@ const CONFIG_SCHEMA: SchemaEntity = {
         },
       ],
     },
+    decorators: {
+      type: 'string',
+      enum: ['legacy', 'standard'],
+    },
+    preserveESM: {
+      type: 'boolean',
+    },
   },
   additionalProperties: false,
 };
@@ -106,6 +107,8 @@ type PackageJSONConfig = {|
   '@parcel/transformer-js'?: {|
     inlineFS?: boolean,
     inlineEnvironment?: boolean | Array<string>,
+    decorators?: 'legacy' | 'standard',
+    preserveESM?: boolean,
   |},
 |};
 
@@ -140,6 +143,8 @@ type TSConfig = {
     jsxImportSource?: string,
     // https://www.typescriptlang.org/tsconfig#experimentalDecorators
     experimentalDecorators?: boolean,
+    // https://www.typescriptlang.org/tsconfig#emitDecoratorMetadata
+    emitDecoratorMetadata?: boolean,
     ...
   },
   ...
@@ -154,7 +159,9 @@ export default (new Transformer({
       jsxImportSource,
       automaticJSXRuntime,
       reactRefresh,
-      decorators;
+      decorators,
+      decoratorsVersion = 'Legacy',
+      emitDecoratorMetadata;
     if (config.isSource) {
       let reactLib;
       if (pkg?.alias && pkg.alias['react']) {
@@ -224,6 +231,7 @@ export default (new Transformer({
 
       isJSX = Boolean(compilerOptions?.jsx || pragma);
       decorators = compilerOptions?.experimentalDecorators;
+      emitDecoratorMetadata = compilerOptions?.emitDecoratorMetadata;
     }
 
     // Check if we should ignore fs calls
@@ -242,6 +250,7 @@ export default (new Transformer({
 
     let inlineEnvironment = config.isSource;
     let inlineFS = !ignoreFS;
+    let preserveESM = false;
     if (result && rootPkg?.['@parcel/transformer-js']) {
       validateSchema.diagnostic(
         CONFIG_SCHEMA,
@@ -261,8 +270,26 @@ export default (new Transformer({
         rootPkg['@parcel/transformer-js']?.inlineEnvironment ??
         inlineEnvironment;
       inlineFS = rootPkg['@parcel/transformer-js']?.inlineFS ?? inlineFS;
+      preserveESM = rootPkg['@parcel/transformer-js']?.preserveESM ?? false;
+
+      let decoratorsOption = rootPkg['@parcel/transformer-js']?.decorators;
+      if (config.isSource && decoratorsOption != null) {
+        decorators = true;
+        decoratorsVersion =
+          decoratorsOption === 'standard' ? 'Standard' : 'Legacy';
+      }
     }
 
+    // Partially dynamic import() and require() specifiers become glob dependencies,
+    // which need the glob resolver to be configured explicitly.
+    let parcelrc = await config.getConfigFrom<{|resolvers?: Array<string>|}>(
+      path.join(options.projectRoot, 'index'),
+      ['.parcelrc'],
+    );
+    let globResolver = Boolean(
+      parcelrc?.contents?.resolvers?.includes('@parcel/resolver-glob'),
+    );
+
     return {
       isJSX,
       automaticJSXRuntime,
@@ -271,8 +298,12 @@ export default (new Transformer({
       pragmaFrag,
       inlineEnvironment,
       inlineFS,
+      preserveESM,
+      globResolver,
       reactRefresh,
       decorators,
+      decoratorsVersion,
+      emitDecoratorMetadata,
     };
   },
   async transform({asset, config, options, logger}) {
@@ -285,12 +316,15 @@ export default (new Transformer({
     let targets;
     if (asset.isSource) {
       if (asset.env.isElectron() && asset.env.engines.electron) {
-        targets = {
-          electron: semver.minVersion(asset.env.engines.electron)?.toString(),
-        };
+        let electron = getMinVersion(asset.env.engines.electron);
+        if (electron != null) {
+          targets = {electron};
+        } else {
+          logger.warn({
+            message: `Invalid electron engine range "${asset.env.engines.electron}".`,
+          });
+        }
       } else if (asset.env.isBrowser() && asset.env.engines.browsers) {
-        targets = {};
-
         let browsers = Array.isArray(asset.env.engines.browsers)
           ? asset.env.engines.browsers
           : [asset.env.engines.browsers];
@@ -301,30 +335,17 @@ export default (new Transformer({
           browsers = [...browsers, ...ESMODULE_BROWSERS];
         }
 
-        browsers = browserslist(browsers);
-        for (let browser of browsers) {
-          let [name, version] = browser.split(' ');
-          if (BROWSER_MAPPING.hasOwnProperty(name)) {
-            name = BROWSER_MAPPING[name];
-            if (!name) {
-              continue;
-            }
-          }
-
-          let [major, minor = '0', patch = '0'] = version
-            .split('-')[0]
-            .split('.');
-          let semverVersion = `${major}.${minor}.${patch}`;
-
-          if (
-            targets[name] == null ||
-            semver.gt(targets[name], semverVersion)
-          ) {
-            targets[name] = semverVersion;
-          }
-        }
+        // Browserslist queries are resolved natively.
+        targets = browsers;
       } else if (asset.env.isNode() && asset.env.engines.node) {
-        targets = {node: semver.minVersion(asset.env.engines.node)?.toString()};
+        let node = getMinVersion(asset.env.engines.node);
+        if (node != null) {
+          targets = {node};
+        } else {
+          logger.warn({
+            message: `Invalid node engine range "${asset.env.engines.node}".`,
+          });
+        }
       }
     }
 
@@ -354,6 +375,14 @@ export default (new Transformer({
 
     let supportsModuleWorkers =
       asset.env.shouldScopeHoist && asset.env.supports('worker-module', true);
+    // Development builds with ES module output can keep import and export statements,
+    // which the packager points at the bundles of the imported modules.
+    let preserveESM =
+      Boolean(config?.preserveESM) &&
+      options.mode === 'development' &&
+      !asset.env.shouldScopeHoist &&
+      asset.env.outputFormat === 'esmodule' &&
+      asset.env.sourceType === 'module';
     let isJSX = Boolean(config?.isJSX);
     if (asset.isSource) {
       if (asset.type === 'ts') {
@@ -368,17 +397,22 @@ export default (new Transformer({
       code: compiledCode,
       map,
       shebang,
+      directives,
+      legal_comments,
       hoist_result,
       needs_esm_helpers,
+      preserved_esm,
       diagnostics,
       used_env,
-    } = transform({
+      profile,
+    } = await transformAsync({
       filename: asset.filePath,
       code,
       module_id: asset.id,
       project_root: options.projectRoot,
       replace_env: !asset.env.isNode(),
       inline_fs: Boolean(config?.inlineFS) && !asset.env.isNode(),
+      glob_dependencies: asset.isSource && Boolean(config?.globResolver),
       insert_node_globals: !asset.env.isNode(),
       is_browser: asset.env.isBrowser(),
       is_worker: asset.env.isWorker(),
@@ -396,17 +430,41 @@ export default (new Transformer({
         !asset.env.isWorklet() &&
         Boolean(config?.reactRefresh),
       decorators: Boolean(config?.decorators),
+      decorators_version: config?.decoratorsVersion ?? 'Legacy',
+      emit_decorator_metadata: Boolean(config?.emitDecoratorMetadata),
       targets,
       source_maps: !!asset.env.sourceMap,
+      minify: asset.env.shouldOptimize,
+      // Legal comments are also returned separately, see `asset.meta.legalComments`.
+      legal_comments: 'Inline',
+      // Input source maps are usually loaded by Parcel and composed with `originalMap` below.
+      // Otherwise, let the native transformer load and compose any `sourceMappingURL` comment.
+      detect_source_map_url: !!asset.env.sourceMap && originalMap == null,
       scope_hoist:
         asset.env.shouldScopeHoist && asset.env.sourceType !== 'script',
+      preserve_esm: preserveESM,
       source_type: asset.env.sourceType === 'script' ? 'Script' : 'Module',
       supports_module_workers: supportsModuleWorkers,
       is_library: asset.env.isLibrary,
       is_esm_output: asset.env.outputFormat === 'esmodule',
       trace_bailouts: options.logLevel === 'verbose',
+      profile: options.logLevel === 'verbose',
     });
 
+    if (profile) {
+      logger.verbose({
+        message: `Transformed ${path.relative(
+          options.projectRoot,
+          asset.filePath,
+        )}:\n${profile
+          .map(
+            ({name, duration_ms, node_count}) =>
+              `  ${name}: ${duration_ms.toFixed(2)}ms (${node_count} nodes)`,
+          )
+          .join('\n')}`,
+      });
+    }
+
     let convertLoc = loc => {
       let location = {
         filePath: asset.filePath,
@@ -508,6 +566,24 @@ export default (new Transformer({
       asset.meta.interpreter = shebang;
     }
 
+    if (directives.length > 0) {
+      asset.meta.directives = directives;
+    }
+
+    // Modules that kept their import and export statements are loaded natively by the
+    // modules that import them, so each one is the entry of its own bundle.
+    if (preserved_esm) {
+      asset.meta.preserveESM = true;
+      asset.bundleBehavior = 'isolated';
+    }
+
+    if (legal_comments.length > 0) {
+      asset.meta.legalComments = legal_comments.map(({text, loc}) => ({
+        text,
+        loc: convertLoc(loc),
+      }));
+    }
+
     for (let env of used_env) {
       asset.invalidateOnEnvChange(env);
     }
@@ -592,6 +668,49 @@ export default (new Transformer({
           meta.placeholder = dep.placeholder;
         }
 
+        // Dependencies of a "use client" module compiled for the server, or a "use server"
+        // module compiled for the browser, belong to the other graph.
+        if (dep.boundary) {
+          meta.boundary = dep.boundary;
+        }
+
+        // Describes the module to generate for glob dependencies, e.g. import.meta.glob().
+        if (dep.glob) {
+          meta.glob = dep.glob;
+        }
+
+        // Magic comments, e.g. import(/* webpackChunkName: "editor" */ './Editor').
+        // The chunk name is used by the default namer, the resource hint by the JS runtime,
+        // and the eager import mode by the default bundler.
+        if (dep.chunk_name != null) {
+          meta.chunkName = dep.chunk_name;
+        }
+
+        if (dep.resource_hint) {
+          meta.resourceHint = dep.resource_hint;
+        }
+
+        // require.resolve() is replaced with the id of the module when packaging.
+        // The module is registered, but only executed if it is required by that id.
+        if (dep.kind === 'RequireResolve') {
+          meta.shouldWrap = true;
+        }
+
+        if (dep.import_mode) {
+          meta.importMode = dep.import_mode;
+        }
+
+        // Kept as an import statement or import() call, which is loaded by the browser
+        // rather than a JS runtime. The packager replaces the placeholder with a bundle path.
+        if (
+          preserved_esm &&
+          (dep.kind === 'Import' ||
+            dep.kind === 'Export' ||
+            dep.kind === 'DynamicImport')
+        ) {
+          meta.preserveESM = true;
+        }
+
         let env;
         if (dep.kind === 'DynamicImport') {
           // https://html.spec.whatwg.org/multipage/webappapis.html#hostimportmoduledynamically(referencingscriptormodule,-modulerequest,-promisecapability)
@@ -664,7 +783,10 @@ export default (new Transformer({
 
         asset.addDependency({
           specifier: dep.specifier,
-          specifierType: dep.kind === 'Require' ? 'commonjs' : 'esm',
+          specifierType:
+            dep.kind === 'Require' || dep.kind === 'RequireResolve'
+              ? 'commonjs'
+              : 'esm',
           loc: convertLoc(dep.loc),
           priority: dep.kind === 'DynamicImport' ? 'lazy' : 'sync',
           isOptional: dep.is_optional,
@@ -688,7 +810,11 @@ export default (new Transformer({
           .map(dep => [dep.meta.placeholder ?? dep.specifier, dep]),
       );
       for (let dep of deps.values()) {
-        dep.symbols.ensure();
+        // Nothing is imported by require.resolve(), but the module can be required by its id,
+        // so its symbols are left cleared, i.e. all of its exports are used.
+        if (dep.meta.kind !== 'RequireResolve') {
+          dep.symbols.ensure();
+        }
       }
 
       for (let {
@@ -699,6 +825,10 @@ export default (new Transformer({
       } of hoist_result.imported_symbols) {
         let dep = deps.get(source);
         if (!dep) continue;
+        // The module can also be required by the result, e.g. require(require.resolve('./a')).
+        if (dep.meta.kind === 'RequireResolve') {
+          dep.symbols.ensure();
+        }
         dep.symbols.set(imported, local, convertLoc(loc));
       }
 
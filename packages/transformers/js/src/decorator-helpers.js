@@ -0,0 +1,266 @@
+var KINDS = ['field', 'accessor', 'method', 'getter', 'setter'];
+var FIELD = 0;
+var ACCESSOR = 1;
+var METHOD = 2;
+var GETTER = 3;
+var SETTER = 4;
+var STATIC = 5;
+
+// Applies standard (2022-03) decorators to a class. This is called while the
+// class is being defined, before its static fields are initialized.
+//
+// Each entry of memberDecs is [decorators, kind, name, ...privateAccess], where
+// kind is one of the constants above, plus STATIC for static members. For private
+// members, name excludes the # and privateAccess is the original method or
+// getter/setter, or a get and set function for fields and accessors.
+//
+// Returns, in order: an initializer for each field and accessor, the decorated get
+// and set functions for each private accessor, the decorated function for each
+// private method, getter or setter, a function running the initializers of instance
+// members if there are any decorated instance methods, getters or setters, the same
+// for static members, and finally the decorated class and a function running its
+// initializers if there are class decorators.
+exports.applyDecs = function(targetClass, memberDecs, classDecs) {
+  var ret = [];
+  var protoInitializers = [];
+  var staticInitializers = [];
+  var hasProto = false;
+  var hasStatic = false;
+
+  for (var i = 0; i < memberDecs.length; i++) {
+    var decInfo = memberDecs[i];
+    var kind = decInfo[1];
+    var isStatic = kind >= STATIC;
+    if (isStatic) {
+      kind -= STATIC;
+    }
+
+    if (kind !== FIELD) {
+      if (isStatic) {
+        hasStatic = true;
+      } else {
+        hasProto = true;
+      }
+    }
+
+    applyMemberDecs(
+      ret,
+      isStatic ? targetClass : targetClass.prototype,
+      decInfo,
+      kind,
+      isStatic,
+      isStatic ? staticInitializers : protoInitializers,
+    );
+  }
+
+  if (hasProto) {
+    ret.push(createRunInitializers(protoInitializers));
+  }
+
+  if (hasStatic) {
+    ret.push(createRunInitializers(staticInitializers));
+  }
+
+  if (classDecs.length > 0) {
+    var classInitializers = [];
+    var newClass = targetClass;
+    var name = targetClass.name;
+    for (var j = classDecs.length - 1; j >= 0; j--) {
+      var finished = {v: false};
+      var value = classDecs[j](newClass, {
+        kind: 'class',
+        name: name,
+        addInitializer: createAddInitializer(classInitializers, finished),
+      });
+      finished.v = true;
+
+      if (value !== undefined) {
+        assertCallable(value, 'class decorators');
+        newClass = value;
+      }
+    }
+
+    ret.push(newClass, function() {
+      for (var k = 0; k < classInitializers.length; k++) {
+        classInitializers[k].call(newClass);
+      }
+    });
+  }
+
+  return ret;
+};
+
+function applyMemberDecs(ret, base, decInfo, kind, isStatic, initializers) {
+  var decs = decInfo[0];
+  var name = decInfo[2];
+  var isPrivate = decInfo.length > 3;
+  var desc, value;
+
+  if (isPrivate) {
+    if (kind === FIELD || kind === ACCESSOR) {
+      desc = {get: decInfo[3], set: decInfo[4]};
+      if (kind === ACCESSOR) {
+        value = desc;
+      }
+    } else if (kind === METHOD) {
+      value = decInfo[3];
+    } else {
+      value = decInfo[3];
+      desc = kind === GETTER ? {get: value} : {set: value};
+    }
+  } else if (kind !== FIELD) {
+    desc = Object.getOwnPropertyDescriptor(base, name);
+    value =
+      kind === METHOD
+        ? desc.value
+        : kind === ACCESSOR
+        ? {get: desc.get, set: desc.set}
+        : kind === GETTER
+        ? desc.get
+        : desc.set;
+  }
+
+  var fieldInitializers = [];
+  for (var i = decs.length - 1; i >= 0; i--) {
+    var finished = {v: false};
+    var context = {
+      kind: KINDS[kind],
+      name: isPrivate ? '#' + name : name,
+      static: isStatic,
+      private: isPrivate,
+      access: createAccess(name, kind, isPrivate, desc, value),
+    };
+    if (kind !== FIELD) {
+      context.addInitializer = createAddInitializer(initializers, finished);
+    }
+
+    var newValue = decs[i](value, context);
+    finished.v = true;
+
+    if (newValue === undefined) {
+      continue;
+    }
+
+    if (kind === ACCESSOR) {
+      if (typeof newValue !== 'object' || newValue === null) {
+        throw new TypeError(
+          'accessor decorators must return an object with get, set, or init properties or void 0',
+        );
+      }
+      assertOptionalFunction(newValue.get, 'accessor.get');
+      assertOptionalFunction(newValue.set, 'accessor.set');
+      assertOptionalFunction(newValue.init, 'accessor.init');
+      value = {
+        get: newValue.get !== undefined ? newValue.get : value.get,
+        set: newValue.set !== undefined ? newValue.set : value.set,
+      };
+      if (newValue.init !== undefined) {
+        fieldInitializers.push(newValue.init);
+      }
+      continue;
+    }
+
+    assertCallable(newValue, KINDS[kind] + ' decorators');
+    if (kind === FIELD) {
+      fieldInitializers.push(newValue);
+    } else {
+      value = newValue;
+    }
+  }
+
+  if (kind === FIELD || kind === ACCESSOR) {
+    ret.push(function(instance, init) {
+      for (var j = 0; j < fieldInitializers.length; j++) {
+        init = fieldInitializers[j].call(instance, init);
+      }
+      return init;
+    });
+  }
+
+  if (kind === FIELD) {
+    return;
+  }
+
+  if (kind === ACCESSOR) {
+    if (isPrivate) {
+      ret.push(value.get, value.set);
+    } else {
+      desc.get = value.get;
+      desc.set = value.set;
+      Object.defineProperty(base, name, desc);
+    }
+  } else if (isPrivate) {
+    ret.push(value);
+  } else {
+    if (kind === METHOD) {
+      desc.value = value;
+    } else if (kind === GETTER) {
+      desc.get = value;
+    } else {
+      desc.set = value;
+    }
+    Object.defineProperty(base, name, desc);
+  }
+}
+
+function createAccess(name, kind, isPrivate, desc, value) {
+  if (isPrivate) {
+    if (kind === FIELD || kind === ACCESSOR) {
+      return {get: desc.get, set: desc.set};
+    }
+    if (kind === METHOD) {
+      return {
+        get: function() {
+          return value;
+        },
+      };
+    }
+    return kind === GETTER ? {get: desc.get} : {set: desc.set};
+  }
+
+  var access = {};
+  if (kind !== SETTER) {
+    access.get = function() {
+      return this[name];
+    };
+  }
+  if (kind === FIELD || kind === ACCESSOR || kind === SETTER) {
+    access.set = function(v) {
+      this[name] = v;
+    };
+  }
+  return access;
+}
+
+function createAddInitializer(initializers, finished) {
+  return function(initializer) {
+    if (finished.v) {
+      throw new Error(
+        'attempted to call addInitializer after decoration was finished',
+      );
+    }
+    assertCallable(initializer, 'An initializer');
+    initializers.push(initializer);
+  };
+}
+
+function createRunInitializers(initializers) {
+  return function(instance) {
+    for (var i = 0; i < initializers.length; i++) {
+      initializers[i].call(instance);
+    }
+    return instance;
+  };
+}
+
+function assertCallable(fn, hint) {
+  if (typeof fn !== 'function') {
+    throw new TypeError(hint + ' must return a function or void 0');
+  }
+}
+
+function assertOptionalFunction(fn, name) {
+  if (fn !== undefined && typeof fn !== 'function') {
+    throw new TypeError(name + ' must be a function');
+  }
+}
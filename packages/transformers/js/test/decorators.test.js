@@ -0,0 +1,238 @@
+// @flow
+import assert from 'assert';
+import {transformAsync} from '../native';
+import * as helpers from '../src/decorator-helpers';
+
+// Compiles the code with standard decorators and runs it, returning its exports.
+// `log` is available to the code to record the arguments of the decorators.
+async function run(code: string, targets?: {[string]: string}) {
+  let result = await transformAsync({
+    filename: '/index.js',
+    code: Buffer.from(code),
+    module_id: 'abc',
+    project_root: '/',
+    decorators: true,
+    decorators_version: 'Standard',
+    targets,
+  });
+  assert.deepEqual(result.diagnostics, null);
+
+  let log = [];
+  let exports = {};
+  // eslint-disable-next-line no-new-func
+  new Function('require', 'exports', 'log', result.code.toString())(
+    specifier =>
+      specifier === '@parcel/transformer-js/src/decorator-helpers.js'
+        ? helpers
+        : // $FlowFixMe[unsupported-syntax]
+          require(specifier),
+    exports,
+    log,
+  );
+  return {exports, log};
+}
+
+const CONTEXTS = `
+  function dec(value, context) {
+    let entry = {
+      kind: context.kind,
+      name: context.name,
+      addInitializer: typeof context.addInitializer,
+    };
+    if (context.kind !== 'class') {
+      entry.static = context.static;
+      entry.private = context.private;
+      entry.access = Object.keys(context.access);
+    }
+    log.push(entry);
+  }
+`;
+
+describe('standard decorators', () => {
+  it('should pass the context of each member and class', async () => {
+    let {log} = await run(`
+      ${CONTEXTS}
+      @dec class Foo {
+        @dec method() {}
+        @dec get getter() {}
+        @dec set setter(v) {}
+        @dec field = 1;
+        @dec accessor acc = 2;
+        @dec static staticMethod() {}
+        @dec static staticField;
+        @dec #privateMethod() {}
+        @dec #privateField;
+        @dec accessor #privateAcc;
+      }
+    `);
+
+    let member = (kind, name, isStatic = false, isPrivate = false) => ({
+      kind,
+      name,
+      addInitializer: kind === 'field' ? 'undefined' : 'function',
+      static: isStatic,
+      private: isPrivate,
+      access:
+        kind === 'field' || kind === 'accessor'
+          ? ['get', 'set']
+          : kind === 'setter'
+          ? ['set']
+          : ['get'],
+    });
+    assert.deepEqual(log, [
+      member('method', 'method'),
+      member('getter', 'getter'),
+      member('setter', 'setter'),
+      member('field', 'field'),
+      member('accessor', 'acc'),
+      member('method', 'staticMethod', true),
+      member('field', 'staticField', true),
+      member('method', '#privateMethod', false, true),
+      member('field', '#privateField', false, true),
+      member('accessor', '#privateAcc', false, true),
+      {kind: 'class', name: 'Foo', addInitializer: 'function'},
+    ]);
+  });
+
+  it('should replace methods and initialize fields', async () => {
+    let {exports} = await run(`
+      function double(method) {
+        return function(...args) {
+          return method.apply(this, args) * 2;
+        };
+      }
+      function add(value, context) {
+        return init => init + 1;
+      }
+      class Foo {
+        @add x = 1;
+        @add #y = 2;
+        @double method() { return this.x; }
+        @double #secret() { return this.#y; }
+        @double static staticMethod() { return 3; }
+        get y() { return this.#secret(); }
+      }
+      exports.foo = new Foo();
+      exports.Foo = Foo;
+    `);
+
+    assert.equal(exports.foo.x, 2);
+    assert.equal(exports.foo.method(), 4);
+    assert.equal(exports.foo.y, 6);
+    assert.equal(exports.Foo.staticMethod(), 6);
+  });
+
+  it('should run initializers added by decorators', async () => {
+    let {exports} = await run(`
+      function register(value, context) {
+        context.addInitializer(function() {
+          (this.registered || (this.registered = [])).push(context.name);
+        });
+      }
+      @register class Foo {
+        @register method() {}
+        @register static staticMethod() {}
+      }
+      exports.Foo = Foo;
+      exports.foo = new Foo();
+    `);
+
+    assert.deepEqual(exports.Foo.registered, ['staticMethod', 'Foo']);
+    assert.deepEqual(exports.foo.registered, ['method']);
+  });
+
+  it('should replace the class', async () => {
+    let {exports} = await run(`
+      function replace(value, context) {
+        return class extends value {
+          replaced = true;
+        };
+      }
+      @replace class Foo {}
+      exports.Foo = Foo;
+    `);
+
+    assert.equal(new exports.Foo().replaced, true);
+  });
+
+  it('should compile accessors', async () => {
+    let {exports} = await run(`
+      function logged(value, context) {
+        return {
+          get() {
+            return 'got ' + value.get.call(this);
+          },
+          set(v) {
+            value.set.call(this, v + 1);
+          },
+          init(v) {
+            return v * 10;
+          },
+        };
+      }
+      class Foo {
+        accessor plain = 1;
+        static accessor ['computed'] = 2;
+        accessor #secret = 3;
+        @logged accessor decorated = 4;
+        @logged accessor #decoratedPrivate = 5;
+        get secret() { return this.#secret; }
+        get decoratedPrivate() { return this.#decoratedPrivate; }
+        set decoratedPrivate(v) { this.#decoratedPrivate = v; }
+      }
+      exports.Foo = Foo;
+      exports.foo = new Foo();
+    `);
+
+    let {Foo, foo} = exports;
+    assert.equal(foo.plain, 1);
+    foo.plain = 10;
+    assert.equal(foo.plain, 10);
+    assert.equal(
+      typeof Object.getOwnPropertyDescriptor(Foo.prototype, 'plain').get,
+      'function',
+    );
+    assert.equal(Foo.computed, 2);
+    assert.equal(foo.secret, 3);
+    assert.equal(foo.decorated, 'got 40');
+    foo.decorated = 1;
+    assert.equal(foo.decorated, 'got 2');
+    assert.equal(foo.decoratedPrivate, 'got 50');
+    foo.decoratedPrivate = 1;
+    assert.equal(foo.decoratedPrivate, 'got 2');
+  });
+
+  it('should decorate private members when class fields are compiled', async () => {
+    let {exports, log} = await run(
+      `
+        ${CONTEXTS}
+        function add(value, context) {
+          if (context.kind === 'field') {
+            return init => init + 1;
+          }
+          return function(...args) {
+            return value.apply(this, args) + 1;
+          };
+        }
+        class Foo {
+          @dec @add #x = 1;
+          @dec @add #method() { return this.#x; }
+          accessor y = 2;
+          get x() { return this.#method(); }
+        }
+        exports.foo = new Foo();
+      `,
+      {chrome: '60'},
+    );
+
+    assert.deepEqual(
+      log.map(context => [context.kind, context.name, context.private]),
+      [
+        ['field', '#x', true],
+        ['method', '#method', true],
+      ],
+    );
+    assert.equal(exports.foo.x, 3);
+    assert.equal(exports.foo.y, 2);
+  });
+});
@@ -0,0 +1,50 @@
+// @flow
+import assert from 'assert';
+import {transformAsync, transformMany} from '../native';
+
+function config(filename: string, code: string) {
+  return {
+    filename,
+    code: Buffer.from(code),
+    module_id: 'abc',
+    project_root: '/',
+    is_browser: true,
+  };
+}
+
+describe('native transformer', () => {
+  it('transformAsync should resolve with the result', async () => {
+    let result = await transformAsync(config('/a.js', 'require("./b");'));
+    assert.deepEqual(
+      result.dependencies.map(dep => dep.specifier),
+      ['./b'],
+    );
+  });
+
+  it('transformMany should return the results in order', async () => {
+    let results = await transformMany([
+      config('/a.js', 'require("./a-dep");'),
+      config('/b.js', 'require("./b-dep");'),
+    ]);
+    assert.deepEqual(
+      results.map(result => result.dependencies[0].specifier),
+      ['./a-dep', './b-dep'],
+    );
+  });
+
+  it('transformMany should report invalid items in place of their result', async () => {
+    let results = await transformMany([
+      config('/a.js', 'require("./a-dep");'),
+      42,
+      {filename: 42},
+      config('/b.js', 'export {'),
+    ]);
+    assert.equal(results.length, 4);
+    assert.equal(results[0].dependencies[0].specifier, './a-dep');
+    assert(results[1] instanceof Error);
+    assert.equal(results[1].message, 'Expected an object at index 1');
+    assert(results[2] instanceof Error);
+    // Syntax errors are diagnostics, rather than errors.
+    assert.equal(results[3].diagnostics[0].severity, 'Error');
+  });
+});